@@ -1,9 +1,24 @@
-advent_of_code::solution!(1);
+advent_of_code::solution!(1, schema = schema);
+
+use anyhow::ensure;
+use std::fmt::Display;
 
 #[derive(Debug)]
-enum Day1Err {
+pub enum Day1Err {
     NoCharacters,
     ParseErr,
+    /// Wraps another error with the (one-based) line number it occurred on.
+    Line(usize, Box<Day1Err>),
+}
+
+impl Display for Day1Err {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Day1Err::NoCharacters => write!(f, "line contains no digits"),
+            Day1Err::ParseErr => write!(f, "could not parse calibration value"),
+            Day1Err::Line(line, e) => write!(f, "line {line}: {e}"),
+        }
+    }
 }
 
 fn take_digits(s: &str) -> Result<u32, Day1Err> {
@@ -18,8 +33,16 @@ fn take_digits(s: &str) -> Result<u32, Day1Err> {
     final_str.parse::<u32>().map_err(|_| Day1Err::ParseErr)
 }
 
-fn process(input: &str) -> u32 {
-    input.lines().map(|l| take_digits(l).unwrap()).sum()
+fn sum_lines(input: &str, f: impl Fn(&str) -> Result<u32, Day1Err>) -> Result<u32, Day1Err> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(ix, l)| f(l).map_err(|e| Day1Err::Line(ix + 1, Box::new(e))))
+        .sum()
+}
+
+fn process(input: &str) -> Result<u32, Day1Err> {
+    sum_lines(input, take_digits)
 }
 
 fn take_digits_with_spelled_nums(s: &str) -> Result<u32, Day1Err> {
@@ -36,21 +59,39 @@ fn take_digits_with_spelled_nums(s: &str) -> Result<u32, Day1Err> {
     take_digits(replaced_str.as_str())
 }
 
-fn process_p2(input: &str) -> u32 {
-    input
-        .lines()
-        .map(|l| take_digits_with_spelled_nums(l).unwrap())
-        .sum()
+fn process_p2(input: &str) -> Result<u32, Day1Err> {
+    sum_lines(input, take_digits_with_spelled_nums)
+}
+
+const SPELLED_DIGITS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Every line is lowercase letters and digits, with at least one digit, spelled out or not.
+pub fn schema(input: &str) -> anyhow::Result<()> {
+    for (ix, line) in input.lines().enumerate() {
+        ensure!(
+            line.chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()),
+            "line {}: `{line}` is not just lowercase letters and digits",
+            ix + 1
+        );
+        ensure!(
+            line.chars().any(|c| c.is_ascii_digit())
+                || SPELLED_DIGITS.iter().any(|d| line.contains(d)),
+            "line {}: `{line}` has no digits",
+            ix + 1
+        );
+    }
+    Ok(())
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let res = process(input);
-    Some(res)
+pub fn part_one(input: &str) -> Result<u32, Day1Err> {
+    process(input)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let res = process_p2(input);
-    Some(res)
+pub fn part_two(input: &str) -> Result<u32, Day1Err> {
+    process_p2(input)
 }
 
 #[cfg(test)]
@@ -62,7 +103,7 @@ mod tests {
         let result = part_one(&advent_of_code::template::read_file_part(
             "examples", DAY, 1,
         ));
-        assert_eq!(result, Some(142));
+        assert_eq!(result.unwrap(), 142);
     }
 
     #[test]
@@ -70,6 +111,31 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file_part(
             "examples", DAY, 2,
         ));
-        assert_eq!(result, Some(281));
+        assert_eq!(result.unwrap(), 281);
+    }
+
+    #[test]
+    fn test_reports_bad_line() {
+        let result = part_one("1abc2\nabc");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "line 2: line contains no digits"
+        );
+    }
+
+    #[test]
+    fn test_schema() {
+        for part in [1, 2] {
+            let input = advent_of_code::template::read_file_part("examples", DAY, part);
+            assert!(schema(&input).is_ok());
+        }
+        assert_eq!(
+            schema("1abc2\nab c").unwrap_err().to_string(),
+            "line 2: `ab c` is not just lowercase letters and digits"
+        );
+        assert_eq!(
+            schema("1abc2\nabc").unwrap_err().to_string(),
+            "line 2: `abc` has no digits"
+        );
     }
 }
@@ -1,15 +1,9 @@
 advent_of_code::solution!(2);
 
-use regex::Regex;
+use advent_of_code::parse::{lines, record, unsigned_ints_exact};
+use anyhow::{bail, Context, Result};
 use std::fmt::Display;
 
-#[derive(Debug)]
-enum Day2Err {
-    ParseLineError,
-    BadRegex,
-    BadInput,
-}
-
 #[derive(Debug)]
 struct GamePart1 {
     id: u32,
@@ -23,8 +17,9 @@ impl GamePart1 {
         red >= self.max_red && green >= self.max_green && blue >= self.max_blue
     }
 
-    fn calc_power(&self) -> u32 {
-        self.max_red * self.max_green * self.max_blue
+    /// The product of three `u32`s always fits in a `u128`.
+    fn calc_power(&self) -> u128 {
+        u128::from(self.max_red) * u128::from(self.max_green) * u128::from(self.max_blue)
     }
 }
 
@@ -38,31 +33,25 @@ impl Display for GamePart1 {
     }
 }
 
-fn parse_input_line(line: &str) -> Result<GamePart1, Day2Err> {
-    let (game, rest) = line.split_once(':').ok_or(Day2Err::ParseLineError)?;
-    let game_id = game
-        .matches(char::is_numeric)
-        .collect::<Vec<_>>()
-        .join("")
-        .parse::<u32>()
-        .map_err(|_| Day2Err::ParseLineError)?;
-    let re = Regex::new(r"(\d+) (blue|red|green)").map_err(|_| Day2Err::BadRegex)?;
+fn parse_input_line(line: &str) -> Result<GamePart1> {
+    let (game, rest) = record(line)?;
+    let [game_id] = unsigned_ints_exact::<u32, 1>(game)?;
     let mut max_red = 0;
     let mut max_blue = 0;
     let mut max_green = 0;
-    for (_, [count, color]) in re.captures_iter(rest).map(|c| c.extract()) {
+    for draw in rest.split([',', ';']) {
+        let (count, color) = draw
+            .trim()
+            .split_once(' ')
+            .with_context(|| format!("expected `<count> <color>`, found `{draw}`"))?;
+        let count = count
+            .parse::<u32>()
+            .with_context(|| format!("`{count}` is not a valid count"))?;
         match color {
-            "red" => {
-                max_red = max_red.max(count.parse::<u32>().map_err(|_| Day2Err::ParseLineError)?)
-            }
-            "green" => {
-                max_green =
-                    max_green.max(count.parse::<u32>().map_err(|_| Day2Err::ParseLineError)?)
-            }
-            "blue" => {
-                max_blue = max_blue.max(count.parse::<u32>().map_err(|_| Day2Err::ParseLineError)?)
-            }
-            _ => return Err(Day2Err::BadInput),
+            "red" => max_red = max_red.max(count),
+            "green" => max_green = max_green.max(count),
+            "blue" => max_blue = max_blue.max(count),
+            _ => bail!("unknown color `{color}`"),
         }
     }
     Ok(GamePart1 {
@@ -73,24 +62,18 @@ fn parse_input_line(line: &str) -> Result<GamePart1, Day2Err> {
     })
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let res = input
-        .lines()
-        .filter_map(|l| {
-            parse_input_line(l)
-                .map(|g| if g.possible(12, 13, 14) { g.id } else { 0 })
-                .map_or_else(|_| None, Some)
-        })
-        .sum();
-    Some(res)
+pub fn part_one(input: &str) -> Result<u64> {
+    let games = lines(input, parse_input_line)?;
+    Ok(games
+        .iter()
+        .filter(|g| g.possible(12, 13, 14))
+        .map(|g| u64::from(g.id))
+        .sum())
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let res = input
-        .lines()
-        .filter_map(|l| parse_input_line(l).map_or_else(|_| None, |g| Some(g.calc_power())))
-        .sum();
-    Some(res)
+pub fn part_two(input: &str) -> Result<u128> {
+    let games = lines(input, parse_input_line)?;
+    Ok(games.iter().map(GamePart1::calc_power).sum())
 }
 
 #[cfg(test)]
@@ -100,12 +83,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(8));
+        assert_eq!(result.unwrap(), 8);
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(2286));
+        assert_eq!(result.unwrap(), 2286);
     }
 }
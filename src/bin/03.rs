@@ -1,7 +1,9 @@
 advent_of_code::solution!(3);
 
-use anyhow::Result;
-use regex::Regex;
+use advent_of_code::parse::{lines, unsigned_ints_located};
+use advent_of_code::trace;
+use advent_of_code::viz::{self, Colour, Grid};
+use anyhow::{Context, Result};
 
 enum LineCellVal {
     Number(u32),
@@ -16,23 +18,16 @@ struct LineCell {
 }
 
 fn parse_line(line: &str, row: u32) -> Result<Vec<LineCell>> {
-    let mut res: Vec<LineCell> = vec![];
-    let re = Regex::new(r"(\d+)")?;
-    re.find_iter(line).for_each(|ma| {
-        let start = ma.start();
-        let len = ma.len();
-        let val = ma.as_str().parse::<u32>().unwrap();
-        let cols = (start..(start + len))
-            .map(|x| x.try_into().unwrap())
-            .collect();
-        res.push(LineCell {
-            val: LineCellVal::Number(val),
-            cols,
+    let mut res: Vec<LineCell> = unsigned_ints_located::<u32>(line)?
+        .into_iter()
+        .map(|n| LineCell {
+            val: LineCellVal::Number(n.value),
+            cols: (n.column..(n.column + n.len)).map(|x| x as u32).collect(),
             row,
         })
-    });
+        .collect();
     line.char_indices().for_each(|(ix, c)| {
-        let col = ix.try_into().unwrap();
+        let col = ix as u32;
         if c == '.' {
             res.push(LineCell {
                 val: LineCellVal::Empty,
@@ -50,6 +45,16 @@ fn parse_line(line: &str, row: u32) -> Result<Vec<LineCell>> {
     Ok(res)
 }
 
+fn parse_cells(input: &str) -> Result<Vec<LineCell>> {
+    let mut row = 0;
+    let rows = lines(input, |l| {
+        let cells = parse_line(l, row);
+        row += 1;
+        cells
+    })?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
 fn has_adj_symbol(row: u32, cols: Vec<u32>, cells: &[LineCell]) -> bool {
     let min_minus_1 = if cols[0] == 0 { 0 } else { cols[0] - 1 };
     let max_plus_1 = cols[cols.len() - 1] + 1;
@@ -61,6 +66,8 @@ fn has_adj_symbol(row: u32, cols: Vec<u32>, cells: &[LineCell]) -> bool {
     let down: Vec<(u32, u32)> = (min_minus_1..=max_plus_1).map(|i| (row + 1, i)).collect();
     let same = vec![(row, min_minus_1), (row, max_plus_1)];
     let all_adj = [up, down, same].concat();
+    // every neighbour is looked for among all cells, this is where part one spends its time.
+    let _span = trace::span("symbol lookup");
     all_adj.iter().any(|(row, col)| {
         cells.iter().any(|l| match l.val {
             LineCellVal::Empty => false,
@@ -106,40 +113,57 @@ fn get_adj_nums(row: u32, col: u32, cells: &[LineCell]) -> Option<(u32, u32)> {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let all_cells: Vec<LineCell> = input
-        .lines()
-        .enumerate()
-        .flat_map(|(ix, l)| parse_line(l, ix.try_into().unwrap()).unwrap())
-        .collect();
-    let nums: u32 = all_cells
-        .iter()
-        .filter_map(|c| match c.val {
-            LineCellVal::Empty => None,
-            LineCellVal::Symbol(_) => None,
-            LineCellVal::Number(n) => {
-                has_adj_symbol(c.row, c.cols.clone(), &all_cells).then_some(n)
+/// Adds up `values`, reporting an error rather than overflowing.
+fn checked_sum(mut values: impl Iterator<Item = u64>) -> Result<u64> {
+    values
+        .try_fold(0_u64, u64::checked_add)
+        .context("the sum does not fit in 64 bits")
+}
+
+pub fn part_one(input: &str) -> Result<u64> {
+    let all_cells = trace::in_span("parse", || parse_cells(input))?;
+    viz::frame("part numbers", || {
+        let mut grid = Grid::from_lines(input);
+        for cell in &all_cells {
+            let colour = match cell.val {
+                LineCellVal::Empty => continue,
+                LineCellVal::Symbol(_) => Colour::Yellow,
+                LineCellVal::Number(_)
+                    if has_adj_symbol(cell.row, cell.cols.clone(), &all_cells) =>
+                {
+                    Colour::Green
+                }
+                LineCellVal::Number(_) => Colour::Red,
+            };
+            for col in &cell.cols {
+                grid.highlight(*col as usize, cell.row as usize, colour);
             }
-        })
-        .sum();
-    Some(nums)
+        }
+        grid
+    });
+    let _span = trace::span("find part numbers");
+    checked_sum(all_cells.iter().filter_map(|c| match c.val {
+        LineCellVal::Empty => None,
+        LineCellVal::Symbol(_) => None,
+        LineCellVal::Number(n) => {
+            has_adj_symbol(c.row, c.cols.clone(), &all_cells).then_some(u64::from(n))
+        }
+    }))
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let all_cells: Vec<LineCell> = input
-        .lines()
-        .enumerate()
-        .flat_map(|(ix, l)| parse_line(l, ix.try_into().unwrap()).unwrap())
-        .collect();
-    let matches = all_cells
-        .iter()
-        .filter_map(|c| match c.val {
-            LineCellVal::Symbol('*') => get_adj_nums(c.row, c.cols[0], &all_cells),
-            _ => None,
-        })
-        .map(|(a, b)| a * b)
-        .sum();
-    Some(matches)
+pub fn part_two(input: &str) -> Result<u64> {
+    let all_cells = trace::in_span("parse", || parse_cells(input))?;
+    let _span = trace::span("find gears");
+    checked_sum(
+        all_cells
+            .iter()
+            .filter_map(|c| match c.val {
+                LineCellVal::Symbol('*') => get_adj_nums(c.row, c.cols[0], &all_cells),
+                _ => None,
+            })
+            // the product of two `u32`s always fits.
+            .map(|(a, b)| u64::from(a) * u64::from(b)),
+    )
 }
 
 #[cfg(test)]
@@ -149,12 +173,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(4361));
+        assert_eq!(result.unwrap(), 4361);
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(467835));
+        assert_eq!(result.unwrap(), 467835);
     }
 }
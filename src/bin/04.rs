@@ -2,6 +2,8 @@ advent_of_code::solution!(4);
 
 use std::collections::HashMap;
 
+use advent_of_code::parse::{lines, record, unsigned_ints, unsigned_ints_exact};
+use anyhow::{Context, Result};
 use array_tool::vec::Intersect;
 
 struct Card {
@@ -29,39 +31,26 @@ impl Card {
     }
 }
 
-fn parse_line(line: &str) -> Card {
-    let (card_id, nums) = line.split_once(':').unwrap();
-    let (win, rest) = nums.split_once('|').unwrap();
-    let winning_nums: Vec<u32> = win
-        .trim()
-        .split(' ')
-        .filter_map(|s| s.parse::<u32>().ok())
-        .collect();
-    let card_nums: Vec<u32> = rest
-        .trim()
-        .split(' ')
-        .filter_map(|s| s.parse::<u32>().ok())
-        .collect();
-    let id = card_id
-        .split_once(' ')
-        .unwrap()
-        .1
-        .trim()
-        .parse::<usize>()
-        .unwrap();
-    Card {
+fn parse_line(line: &str) -> Result<Card> {
+    let (card_id, nums) = record(line)?;
+    let (win, rest) = nums
+        .split_once('|')
+        .with_context(|| format!("expected winning numbers and card numbers in `{nums}`"))?;
+    let [id] = unsigned_ints_exact::<usize, 1>(card_id)?;
+    Ok(Card {
         id,
-        winning_nums,
-        card_nums,
-    }
+        winning_nums: unsigned_ints(win)?,
+        card_nums: unsigned_ints(rest)?,
+    })
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    Some(input.lines().map(|l| parse_line(l).calc_score()).sum())
+pub fn part_one(input: &str) -> Result<u32> {
+    let cards = lines(input, parse_line)?;
+    Ok(cards.iter().map(Card::calc_score).sum())
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let cards: Vec<Card> = input.lines().map(parse_line).collect();
+pub fn part_two(input: &str) -> Result<u32> {
+    let cards = lines(input, parse_line)?;
     let mut card_counts = HashMap::<usize, u32>::new();
     for card in &cards {
         card_counts.insert(card.id, 1);
@@ -77,7 +66,7 @@ pub fn part_two(input: &str) -> Option<u32> {
             card_counts.entry(id).and_modify(|v| *v += existing_count);
         }
     }
-    Some(card_counts.values().sum())
+    Ok(card_counts.values().sum())
 }
 
 #[cfg(test)]
@@ -87,12 +76,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(13));
+        assert_eq!(result.unwrap(), 13);
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(30));
+        assert_eq!(result.unwrap(), 30);
     }
 }
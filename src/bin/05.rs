@@ -1,177 +1,40 @@
-use std::{collections::BTreeSet, str::FromStr};
-
-advent_of_code::solution!(5);
-
-// PART 2 CODE - https://gist.github.com/shaansheikh/bbda4b79a0fe5a32a484f66fb6cd0cd4
-struct Day5Err;
-
-struct Interval {
-    start: i64,
-    end: i64,
-    offset: i64,
-}
-
-impl FromStr for Interval {
-    type Err = Day5Err;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut nums = s.split(' ').map(|x| x.parse::<i64>());
-        let dest = nums.next().unwrap().map_err(|_| Day5Err)?;
-        let start = nums.next().unwrap().map_err(|_| Day5Err)?;
-        let len = nums.next().unwrap().map_err(|_| Day5Err)?;
-        let end = start + len - 1;
-        let offset = dest - start;
-        Ok(Interval { end, start, offset })
-    }
+use std::{fmt::Display, ops::Range, str::FromStr};
+
+use advent_of_code::interval::{IntervalSet, OffsetMap};
+use advent_of_code::parse::{blocks, record, unsigned_ints, unsigned_ints_exact};
+use anyhow::{bail, ensure, Context, Result};
+use rayon::prelude::*;
+
+advent_of_code::solution!(
+    5,
+    variants = [
+        (2, "brute", part_two_brute),
+        (2, "composed", part_two_composed)
+    ]
+);
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum MapType {
+    Seed,
+    Soil,
+    Fertilizer,
+    Water,
+    Light,
+    Temp,
+    Humidity,
+    Location,
 }
 
-impl Interval {
-    fn new(start: i64, len: i64) -> Self {
-        Interval {
-            start,
-            end: start + len + 1,
-            offset: 0,
-        }
-    }
-
-    fn contains(&self, point: i64) -> bool {
-        self.start <= point && point <= self.end
-    }
-
-    fn map(&self, point: i64) -> i64 {
-        point + self.offset
-    }
-
-    fn outputs(&self, mapped_point: i64) -> bool {
-        self.start + self.offset <= mapped_point && mapped_point <= self.end + self.offset
-    }
+#[derive(Debug)]
+pub struct ParseMapTypeErr(String);
 
-    fn undo_map(&self, mapped_point: i64) -> i64 {
-        mapped_point - self.offset
+impl Display for ParseMapTypeErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown map type `{}`", self.0)
     }
 }
 
-struct IntervalList(Vec<Interval>);
-
-impl IntervalList {
-    fn process(&self, point: i64) -> i64 {
-        for i in &self.0 {
-            if i.contains(point) {
-                return i.map(point);
-            }
-        }
-        point
-    }
-
-    fn undo_proc(&self, outputs: &mut BTreeSet<i64>) -> BTreeSet<i64> {
-        let mut potentials: BTreeSet<i64> = BTreeSet::new();
-        for i in &self.0 {
-            for o in outputs.iter() {
-                if i.outputs(*o) {
-                    potentials.insert(i.undo_map(*o));
-                }
-            }
-        }
-        outputs.append(&mut potentials);
-        outputs.to_owned()
-    }
-
-    fn filter(&self, points: BTreeSet<i64>) -> BTreeSet<i64> {
-        let mut res: BTreeSet<i64> = BTreeSet::new();
-        for p in points.iter() {
-            if self.0.iter().any(|i| i.contains(*p)) {
-                res.insert(*p);
-            }
-        }
-        res
-    }
-
-    fn boundaries(&self, candidate_points: &mut BTreeSet<i64>) -> BTreeSet<i64> {
-        for i in self.0.iter() {
-            candidate_points.insert(i.start);
-            candidate_points.insert(i.end);
-        }
-        candidate_points.to_owned()
-    }
-}
-
-fn parse(input: &str) -> (IntervalList, Vec<IntervalList>) {
-    let mut lines = input.lines();
-    let seeds_line = lines.next().unwrap();
-    let seeds_unparsed: Vec<i64> = seeds_line.split_once(": ").map_or(vec![], |l| {
-        l.1.split(char::is_whitespace)
-            .filter_map(|x| match x {
-                "" => None,
-                s => s.parse::<i64>().ok(),
-            })
-            .collect()
-    });
-    let seeds_intervals: Vec<Interval> = seeds_unparsed
-        .chunks(2)
-        .map(|chunk| {
-            let start = *chunk.first().unwrap();
-            let range = *chunk.last().unwrap();
-            Interval::new(start, range)
-        })
-        .collect::<Vec<Interval>>();
-    let seeds_intervals = IntervalList(seeds_intervals);
-
-    let mut curr: Vec<Interval> = Vec::new();
-    let mut int_lists: Vec<IntervalList> = Vec::new();
-    for l in lines {
-        if l.is_empty() {
-            if !curr.is_empty() {
-                int_lists.push(IntervalList(curr));
-            }
-            curr = Vec::new();
-        } else if let Ok(i) = l.parse::<Interval>() {
-            curr.push(i);
-        }
-    }
-
-    (seeds_intervals, int_lists)
-}
-
-fn do_part_two(input: &str) -> i64 {
-    let (seed_ints, mut ivs) = parse(input);
-    let mut candidate_set: BTreeSet<i64> = BTreeSet::new();
-    ivs.reverse();
-    for i in &ivs {
-        candidate_set = i.undo_proc(&mut candidate_set);
-        candidate_set = i.boundaries(&mut candidate_set);
-    }
-
-    candidate_set = seed_ints.filter(candidate_set);
-
-    let mut curr = i64::MAX;
-
-    ivs.reverse();
-    for s in candidate_set.iter() {
-        let mut temp = *s;
-        for i in ivs.iter() {
-            temp = i.process(temp);
-        }
-        if temp < curr {
-            curr = temp
-        }
-    }
-    curr
-}
-
-// END PART 2 CODE
-
-#[derive(Eq, PartialEq, Debug, Clone, Copy, PartialOrd, Ord)]
-enum MapType {
-    Seed = 7,
-    Soil = 6,
-    Fertilizer = 5,
-    Water = 4,
-    Light = 3,
-    Temp = 2,
-    Humidity = 1,
-    Location = 0,
-}
-
-struct ParseMapTypeErr;
+impl std::error::Error for ParseMapTypeErr {}
 
 impl FromStr for MapType {
     type Err = ParseMapTypeErr;
@@ -185,358 +48,369 @@ impl FromStr for MapType {
             "temperature" => Ok(MapType::Temp),
             "humidity" => Ok(MapType::Humidity),
             "location" => Ok(MapType::Location),
-            _ => Err(ParseMapTypeErr),
+            x => Err(ParseMapTypeErr(x.to_string())),
         }
     }
 }
 
-#[derive(Eq, PartialEq, Clone)]
-struct SMap2 {
-    source_start: u64,
-    dest_start: u64,
-    range: u64,
+impl Display for MapType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MapType::Seed => "seed",
+            MapType::Soil => "soil",
+            MapType::Fertilizer => "fertilizer",
+            MapType::Water => "water",
+            MapType::Light => "light",
+            MapType::Temp => "temperature",
+            MapType::Humidity => "humidity",
+            MapType::Location => "location",
+        };
+        write!(f, "{name}")
+    }
 }
 
-impl SMap2 {
-    fn run_map(&self, input: u64) -> u64 {
-        let range_upper_bound_excl = self.source_start + self.range;
-        if range_upper_bound_excl > input && self.source_start <= input {
-            // dest 50, source 55, 55 -> 50, -5, dest - source
-            let s: i64 = self.source_start.try_into().unwrap();
-            let d: i64 = self.dest_start.try_into().unwrap();
-            let diff: i64 = d - s;
-            let i: i64 = input.try_into().unwrap();
-            return (i + diff).try_into().unwrap();
-        }
-        input
-    }
+/// One `x-to-y map:` block.
+struct Stage {
+    from: MapType,
+    to: MapType,
+    map: OffsetMap,
 }
 
-struct Instructions2 {
-    seeds: Vec<u64>,
-    seed_to_soil: Vec<SMap2>,
-    soil_to_fert: Vec<SMap2>,
-    fert_to_water: Vec<SMap2>,
-    water_to_light: Vec<SMap2>,
-    light_to_temp: Vec<SMap2>,
-    temp_to_hum: Vec<SMap2>,
-    hum_to_loc: Vec<SMap2>,
+/// The seeds and a complete, ordered chain of maps from seeds to locations.
+pub struct Almanac {
+    seeds: Vec<i64>,
+    stages: Vec<Stage>,
 }
 
-fn get_next(xs: &[SMap2], curr: u64) -> u64 {
-    xs.iter()
-        .find_map(|x| {
-            let y = x.run_map(curr);
-            if y != curr {
-                Some(y)
-            } else {
-                None
-            }
-        })
-        .unwrap_or(curr)
+fn parse_from_to(l: &str) -> Result<(MapType, MapType)> {
+    let (from, to) = l
+        .strip_suffix(" map:")
+        .and_then(|x| x.split_once("-to-"))
+        .with_context(|| format!("expected an `x-to-y map:` header, found `{l}`"))?;
+    Ok((from.parse::<MapType>()?, to.parse::<MapType>()?))
 }
 
-impl Instructions2 {
-    fn new(seeds: Vec<u64>) -> Self {
-        Instructions2 {
-            seeds,
-            seed_to_soil: vec![],
-            soil_to_fert: vec![],
-            fert_to_water: vec![],
-            water_to_light: vec![],
-            light_to_temp: vec![],
-            temp_to_hum: vec![],
-            hum_to_loc: vec![],
-        }
-    }
+/// The largest number an almanac may hold. Ranges end and values move at most this far again, so
+/// none of the arithmetic on them can overflow an `i64`.
+const MAX_NUMBER: i64 = 1 << 60;
 
-    fn get_final_locations_2(&self) -> Vec<u64> {
-        self.seeds
-            .iter()
-            .map(|s| {
-                let mut curr = *s;
-                curr = get_next(&self.seed_to_soil, curr);
-                curr = get_next(&self.soil_to_fert, curr);
-                curr = get_next(&self.fert_to_water, curr);
-                curr = get_next(&self.water_to_light, curr);
-                curr = get_next(&self.light_to_temp, curr);
-                curr = get_next(&self.temp_to_hum, curr);
-                get_next(&self.hum_to_loc, curr)
-            })
-            .collect()
+fn ensure_in_bounds(numbers: &[i64]) -> Result<()> {
+    match numbers.iter().find(|n| **n > MAX_NUMBER) {
+        Some(n) => bail!("{n} is larger than the supported maximum of {MAX_NUMBER}"),
+        None => Ok(()),
     }
+}
 
-    // fn get_min_final_loc(&self) -> Option<u64> {
-    //     let ranges_loc: Vec<TypedRange> = self.hum_to_loc.iter().map(|s| TypedRange::new(MapType::Location, s.clone())).collect();
-    //     let ranges_hum: Vec<TypedRange> = self.temp_to_hum.iter().map(|s| TypedRange::new(MapType::Humidity, s.clone())).collect();
-    //     let ranges_temp: Vec<TypedRange> = self.light_to_temp.iter().map(|s| TypedRange::new(MapType::Temp, s.clone())).collect();
-    //     let ranges_light: Vec<TypedRange> = self.water_to_light.iter().map(|s| TypedRange::new(MapType::Light, s.clone())).collect();
-    //     let ranges_wat: Vec<TypedRange> = self.fert_to_water.iter().map(|s| TypedRange::new(MapType::Water, s.clone())).collect();
-    //     let ranges_fert: Vec<TypedRange> = self.soil_to_fert.iter().map(|s| TypedRange::new(MapType::Fertilizer, s.clone())).collect();
-    //     let ranges_soil: Vec<TypedRange> = self.seed_to_soil.iter().map(|s| TypedRange::new(MapType::Soil, s.clone())).collect();
-    //     let mut all_ranges: Vec<TypedRange> = [ranges_loc, ranges_hum, ranges_temp, ranges_light, ranges_wat, ranges_fert, ranges_soil].concat();
-    //     all_ranges.sort();
-    //     let mut iterator = all_ranges.iter();
-
-    //     let mut res: Option<u64> = None;
-    //     while res.is_none() {
-    //         let next = iterator.next();
-    //         if next.is_none() {
-    //             break;
-    //         }
-
-    //         res = test_option(next.unwrap(), &self);
-    //     }
-
-    //     res
-    // }
+/// Reads a `destination source length` line as the range it moves and how far it moves it.
+fn parse_piece(line: &str) -> Result<(Range<i64>, i64)> {
+    let [dest, start, len] = unsigned_ints_exact::<i64, 3>(line)?;
+    ensure_in_bounds(&[dest, start, len])?;
+    Ok((start..start + len, dest - start))
 }
 
-fn parse_from_to(l: &str) -> Option<(MapType, MapType)> {
-    l.split_once(' ')
-        .and_then(|x| x.0.split_once("-to-"))
-        .and_then(
-            |(from, to)| match (from.parse::<MapType>(), to.parse::<MapType>()) {
-                (Ok(f), Ok(t)) => Some((f, t)),
-                _ => None,
-            },
-        )
+fn parse_stage(block: &str) -> Result<Stage> {
+    let mut lines = block.lines();
+    let header = lines.next().unwrap_or_default().trim();
+    let (from, to) = parse_from_to(header)?;
+    let map = lines
+        .map(parse_piece)
+        .collect::<Result<Vec<_>>>()
+        .and_then(OffsetMap::from_pieces)
+        .with_context(|| format!("`{header}`"))?;
+    Ok(Stage { from, to, map })
 }
 
-fn parse_map_line_2(l: &str) -> SMap2 {
-    let nums: Vec<u64> = l
-        .split(' ')
-        .filter_map(|x| match x {
-            "" => None,
-            x => x.parse::<u64>().ok(),
-        })
-        .collect();
-    SMap2 {
-        source_start: nums[1],
-        dest_start: nums[0],
-        range: nums[2],
+impl FromStr for Almanac {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let blocks = blocks(input);
+        let Some((seeds_block, stage_blocks)) = blocks.split_first() else {
+            bail!("input is empty");
+        };
+        let (key, seeds) = record(seeds_block)?;
+        ensure!(key == "seeds", "expected the `seeds:` line, found `{key}:`");
+        let seeds = unsigned_ints(seeds)?;
+        ensure_in_bounds(&seeds).context("`seeds:`")?;
+
+        let stages = stage_blocks
+            .iter()
+            .map(|block| parse_stage(block))
+            .collect::<Result<Vec<Stage>>>()?;
+
+        // each map has to pick up where the one before it left off, without going round in circles.
+        let mut reached = vec![MapType::Seed];
+        for stage in &stages {
+            let last = reached[reached.len() - 1];
+            ensure!(
+                stage.from == last,
+                "the `{}-to-{}` map follows a map to {last}",
+                stage.from,
+                stage.to
+            );
+            ensure!(
+                !reached.contains(&stage.to),
+                "the `{}-to-{}` map goes back to an earlier category",
+                stage.from,
+                stage.to
+            );
+            reached.push(stage.to);
+        }
+        let last = reached[reached.len() - 1];
+        ensure!(
+            last == MapType::Location,
+            "the maps stop at {last} and never reach location"
+        );
+
+        Ok(Almanac { seeds, stages })
     }
 }
 
-fn parse_instructions(input: &str) -> Instructions2 {
-    let mut lines = input.lines();
-    let seeds_line = lines.next().unwrap();
-    let seeds: Vec<u64> = seeds_line.split_once(": ").map_or(vec![], |l| {
-        l.1.split(char::is_whitespace)
-            .filter_map(|x| match x {
-                "" => None,
-                s => s.parse::<u64>().ok(),
-            })
-            .collect()
-    });
-
-    let mut curr_map: Option<(MapType, MapType)> = None;
-    let mut builder = Instructions2::new(seeds);
-
-    for next_line in lines {
-        let is_nums = next_line.starts_with(char::is_numeric);
-        let is_spec_line = next_line.starts_with(char::is_alphabetic);
-
-        match (&curr_map, is_nums, is_spec_line) {
-            (Some(_), false, false) => curr_map = None,
-            (None, false, true) => {
-                curr_map = parse_from_to(next_line);
-            }
-            (Some((MapType::Seed, MapType::Soil)), true, false) => {
-                let m = parse_map_line_2(next_line);
-                builder.seed_to_soil.push(m);
-            }
-            (Some((MapType::Soil, MapType::Fertilizer)), true, false) => {
-                let m = parse_map_line_2(next_line);
-                builder.soil_to_fert.push(m);
-            }
-            (Some((MapType::Fertilizer, MapType::Water)), true, false) => {
-                let m = parse_map_line_2(next_line);
-                builder.fert_to_water.push(m);
-            }
-            (Some((MapType::Water, MapType::Light)), true, false) => {
-                let m = parse_map_line_2(next_line);
-                builder.water_to_light.push(m);
-            }
-            (Some((MapType::Light, MapType::Temp)), true, false) => {
-                let m = parse_map_line_2(next_line);
-                builder.light_to_temp.push(m);
-            }
-            (Some((MapType::Temp, MapType::Humidity)), true, false) => {
-                let m = parse_map_line_2(next_line);
-                builder.temp_to_hum.push(m);
-            }
-            (Some((MapType::Humidity, MapType::Location)), true, false) => {
-                let m = parse_map_line_2(next_line);
-                builder.hum_to_loc.push(m);
-            }
-            _ => (),
+impl Almanac {
+    /// Every seed listed, each on its own.
+    pub fn seed_numbers(&self) -> IntervalSet {
+        IntervalSet::from_ranges(self.seeds.iter().map(|s| *s..*s + 1))
+    }
+
+    /// The seeds listed, read as `start length` pairs.
+    pub fn seed_ranges(&self) -> Result<IntervalSet> {
+        ensure!(
+            self.seeds.len().is_multiple_of(2),
+            "seed ranges come in pairs, found {} numbers",
+            self.seeds.len()
+        );
+        Ok(IntervalSet::from_ranges(
+            self.seeds.chunks_exact(2).map(|c| c[0]..c[0] + c[1]),
+        ))
+    }
+
+    /// The maps leading from seeds to `stage`.
+    fn stages_to(&self, stage: MapType) -> Result<&[Stage]> {
+        if stage == MapType::Seed {
+            return Ok(&[]);
         }
+        let ix = self
+            .stages
+            .iter()
+            .position(|s| s.to == stage)
+            .with_context(|| format!("no map leads to {stage}"))?;
+        Ok(&self.stages[..=ix])
+    }
+
+    /// A single map from seed numbers to `stage` numbers.
+    pub fn seed_to(&self, stage: MapType) -> Result<OffsetMap> {
+        Ok(self
+            .stages_to(stage)?
+            .iter()
+            .fold(OffsetMap::identity(), |acc, s| acc.then(&s.map)))
+    }
+
+    /// The `stage` numbers `seeds` end up at.
+    pub fn propagate(&self, seeds: &IntervalSet, stage: MapType) -> Result<IntervalSet> {
+        Ok(self
+            .stages_to(stage)?
+            .iter()
+            .fold(seeds.clone(), |acc, s| s.map.apply(&acc)))
+    }
+
+    /// The lowest `stage` number any of `seeds` ends up at.
+    pub fn lowest_at(&self, seeds: &IntervalSet, stage: MapType) -> Result<i64> {
+        self.propagate(seeds, stage)?
+            .min()
+            .context("there are no seeds to plant")
+    }
+
+    /// The location a single seed ends up at.
+    pub fn location_of(&self, seed: i64) -> i64 {
+        self.stages.iter().fold(seed, |acc, s| s.map.get(acc))
     }
 
-    builder
+    /// Every seed number, listed or not, that ends up at `value` in `stage`.
+    pub fn seeds_reaching(&self, stage: MapType, value: i64) -> Result<IntervalSet> {
+        let target = IntervalSet::from_ranges(std::iter::once(value..value + 1));
+        Ok(self
+            .stages_to(stage)?
+            .iter()
+            .rev()
+            .fold(target, |acc, s| s.map.preimage(&acc)))
+    }
 }
 
-// fn parse_instructions_2(input: &str) -> Instructions2 {
-//     let mut lines = input.lines();
-//     let seeds_line = lines.next().unwrap();
-//     let seeds_unparsed: Vec<u64> = seeds_line.split_once(": ").map_or(vec![], |l| {
-//         l.1.split(char::is_whitespace)
-//             .filter_map(|x| match x {
-//                 "" => None,
-//                 s => s.parse::<u64>().ok(),
-//             })
-//             .collect()
-//     });
-//     let seeds: Vec<u64> = seeds_unparsed
-//         .chunks(2)
-//         .map(|chunk| {
-//             let start = *chunk.first().unwrap();
-//             let range = *chunk.last().unwrap();
-//             let r: Vec<u64> = (start..(start + range)).collect();
-//             r
-//         })
-//         .collect::<Vec<Vec<u64>>>()
-//         .concat();
-
-//     let mut curr_map: Option<(MapType, MapType)> = None;
-//     let mut builder = Instructions2::new(seeds);
-
-//     for next_line in lines {
-//         let is_nums = next_line.starts_with(char::is_numeric);
-//         let is_spec_line = next_line.starts_with(char::is_alphabetic);
-
-//         match (&curr_map, is_nums, is_spec_line) {
-//             (Some(_), false, false) => curr_map = None,
-//             (None, false, true) => {
-//                 curr_map = parse_from_to(next_line);
-//             }
-//             (Some((MapType::Seed, MapType::Soil)), true, false) => {
-//                 let m = parse_map_line_2(next_line);
-//                 builder.seed_to_soil.push(m);
-//             }
-//             (Some((MapType::Soil, MapType::Fertilizer)), true, false) => {
-//                 let m = parse_map_line_2(next_line);
-//                 builder.soil_to_fert.push(m);
-//             }
-//             (Some((MapType::Fertilizer, MapType::Water)), true, false) => {
-//                 let m = parse_map_line_2(next_line);
-//                 builder.fert_to_water.push(m);
-//             }
-//             (Some((MapType::Water, MapType::Light)), true, false) => {
-//                 let m = parse_map_line_2(next_line);
-//                 builder.water_to_light.push(m);
-//             }
-//             (Some((MapType::Light, MapType::Temp)), true, false) => {
-//                 let m = parse_map_line_2(next_line);
-//                 builder.light_to_temp.push(m);
-//             }
-//             (Some((MapType::Temp, MapType::Humidity)), true, false) => {
-//                 let m = parse_map_line_2(next_line);
-//                 builder.temp_to_hum.push(m);
-//             }
-//             (Some((MapType::Humidity, MapType::Location)), true, false) => {
-//                 let m = parse_map_line_2(next_line);
-//                 builder.hum_to_loc.push(m);
-//             }
-//             _ => (),
-//         }
-//     }
-
-//     builder
-// }
-
-pub fn part_one(input: &str) -> Option<u64> {
-    let instructs = parse_instructions(input);
-    let mut final_locations = instructs.get_final_locations_2();
-    final_locations.sort();
-    final_locations.first().copied()
+pub fn part_one(input: &str) -> Result<i64> {
+    let almanac: Almanac = input.parse()?;
+    almanac.lowest_at(&almanac.seed_numbers(), MapType::Location)
 }
 
-pub fn part_two(input: &str) -> Option<i64> {
-    let res = do_part_two(input);
-    Some(res)
+pub fn part_two(input: &str) -> Result<i64> {
+    let almanac: Almanac = input.parse()?;
+    almanac.lowest_at(&almanac.seed_ranges()?, MapType::Location)
+}
+
+/// Part two one seed at a time, to check the interval version against.
+pub fn part_two_brute(input: &str) -> Result<i64> {
+    let almanac: Almanac = input.parse()?;
+    let seeds = almanac.seed_ranges()?;
+    seeds
+        .ranges()
+        .par_iter()
+        .filter_map(|r| {
+            r.clone()
+                .into_par_iter()
+                .map(|seed| almanac.location_of(seed))
+                .min()
+        })
+        .min()
+        .context("there are no seeds to plant")
+}
+
+/// Part two through a single map from seeds to locations, whose lowest value over the seed
+/// ranges sits at the start of one of its pieces.
+pub fn part_two_composed(input: &str) -> Result<i64> {
+    let almanac: Almanac = input.parse()?;
+    let seed_to_location = almanac.seed_to(MapType::Location)?;
+    almanac
+        .seed_ranges()?
+        .split(&seed_to_location)
+        .iter()
+        .map(|(range, offset)| range.start + offset)
+        .min()
+        .context("there are no seeds to plant")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn example() -> Almanac {
+        advent_of_code::template::read_file("examples", DAY)
+            .parse()
+            .unwrap()
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(35));
+        assert_eq!(result.unwrap(), 35);
     }
 
     // i don't get it
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(46));
+        assert_eq!(result.unwrap(), 46);
+    }
+
+    #[test]
+    fn test_part_two_variants() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(part_two_brute(&input).unwrap(), 46);
+        assert_eq!(part_two_composed(&input).unwrap(), 46);
     }
 
     #[test]
     fn test_smap_basic() {
         //50 98 2
-        let smap = SMap2 {
-            dest_start: 50,
-            source_start: 98,
-            range: 2,
-        };
-        let result1 = smap.run_map(98);
-        let result2 = smap.run_map(99);
-        let result3 = smap.run_map(2);
-        let result4 = smap.run_map(100);
+        let smap = OffsetMap::from_pieces([parse_piece("50 98 2").unwrap()]).unwrap();
+        let result1 = smap.get(98);
+        let result2 = smap.get(99);
+        let result3 = smap.get(2);
+        let result4 = smap.get(100);
         assert_eq!(result1, 50);
         assert_eq!(result2, 51);
         assert_eq!(result3, 2);
         assert_eq!(result4, 100);
     }
 
-    // #[test]
-    // fn why_so_slow() {
-    //     let input = &advent_of_code::template::read_file("inputs", DAY);
-    //     let res = part_one(input);
-    //     assert_eq!(res, None);
-    // }
-
     #[test]
     fn test_smap_basic_2() {
-        //50 98 2
-        let smap_1 = SMap2 {
-            source_start: 53,
-            dest_start: 49,
-            range: 8,
-        };
-        let smap_2 = SMap2 {
-            source_start: 11,
-            dest_start: 0,
-            range: 42,
-        };
-        let smap_3 = SMap2 {
-            source_start: 0,
-            dest_start: 42,
-            range: 7,
-        };
-        let smap_4 = SMap2 {
-            source_start: 7,
-            dest_start: 57,
-            range: 4,
-        };
-        let result1 = smap_1.run_map(53);
-        let result2 = smap_2.run_map(53);
-        let result3 = smap_3.run_map(53);
-        let result4 = smap_4.run_map(53);
-        assert!([result1, result2, result3, result4].contains(&49));
+        let results = ["49 53 8", "0 11 42", "42 0 7", "57 7 4"].map(|l| {
+            OffsetMap::from_pieces([parse_piece(l).unwrap()])
+                .unwrap()
+                .get(53)
+        });
+        assert!(results.contains(&49));
     }
 
-    // #[test]
-    // fn test_maps_to_map() {
-    //     let maps = vec![
-    //         SMap2 { dest_start: 50, source_start: 98, range: 2 },
-    //         SMap2 { dest_start: 52, source_start: 50, range: 48 },
-    //     ];
-    //     let hashmap = maps_to_map(&maps);
-    //     assert!(true);
-    // }
+    #[test]
+    fn test_example_seed_locations() {
+        let almanac = example();
+        let seed_to_location = almanac.seed_to(MapType::Location).unwrap();
+        let locations = almanac.seeds.iter().map(|s| seed_to_location.get(*s));
+        assert_eq!(locations.collect::<Vec<_>>(), vec![82, 43, 86, 35]);
+    }
+
+    #[test]
+    fn test_seed_ranges_are_half_open() {
+        let almanac = example();
+        // `79 14` covers seeds 79 to 92, `55 13` covers 55 to 67.
+        let seeds = almanac.seed_ranges().unwrap();
+        assert_eq!(seeds.len(), 27);
+        assert!(seeds.contains(92) && !seeds.contains(93));
+        assert!(seeds.contains(67) && !seeds.contains(68));
+    }
+
+    #[test]
+    fn test_queries_intermediate_stages() {
+        let almanac = example();
+        let seeds = almanac.seed_numbers();
+        // seed 13 is not moved by the first map, so it is also the lowest soil.
+        assert_eq!(almanac.lowest_at(&seeds, MapType::Seed).unwrap(), 13);
+        assert_eq!(almanac.lowest_at(&seeds, MapType::Soil).unwrap(), 13);
+        // seed 13 is at humidity 35 and seed 79 is at location 82.
+        assert_eq!(almanac.lowest_at(&seeds, MapType::Humidity).unwrap(), 35);
+        assert!(almanac
+            .seeds_reaching(MapType::Location, 82)
+            .unwrap()
+            .contains(79));
+    }
+
+    #[test]
+    fn test_seeds_reaching_a_location() {
+        let almanac = example();
+        let ranges = almanac.seed_ranges().unwrap();
+        let found = almanac
+            .seeds_reaching(MapType::Location, 46)
+            .unwrap()
+            .intersection(&ranges);
+        assert_eq!((found.len(), found.min()), (1, Some(82)));
+    }
+
+    #[test]
+    fn test_rejects_broken_chains() {
+        let err = "seeds: 1\n\nseed-to-soil map:\n\nsoil-to-seed map:\n"
+            .parse::<Almanac>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "the `soil-to-seed` map goes back to an earlier category"
+        );
+
+        let err = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nwater-to-light map:\n1 2 3"
+            .parse::<Almanac>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "the `water-to-light` map follows a map to soil"
+        );
+
+        let err = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3"
+            .parse::<Almanac>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "the maps stop at soil and never reach location"
+        );
+
+        let err = "seeds: 1\n\nseed-to-location map:\n1 2 3\n4 3 2"
+            .parse::<Almanac>()
+            .err()
+            .unwrap();
+        assert_eq!(
+            format!("{err:#}"),
+            "`seed-to-location map:`: the ranges 2..5 and 3..5 overlap"
+        );
+    }
 }
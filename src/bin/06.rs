@@ -1,80 +1,192 @@
+use advent_of_code::parse::{record, unsigned_ints};
+use anyhow::{bail, Context, Result};
+
 advent_of_code::solution!(6);
 
 struct RResult {
-    time: u64,
-    distance: u64,
+    time: u128,
+    distance: u128,
 }
 
 impl RResult {
-    fn get_possibilities(&self) -> Vec<u64> {
-        // 0..n..x, where n * (x-n) > d
-        (1..self.time)
-            .filter(|x| *x * (self.time - x) > self.distance)
-            .collect()
+    /// Counts the hold times `n` that beat the record, ie. `n * (time - n) > distance`.
+    ///
+    /// With `time = 2h + p` for a parity `p`, the hold times `h - k` and `h + p + k` both travel
+    /// `h * (h + p) - k * (k + p)`, so they win exactly when `k * (k + p) < h * (h + p) - distance`.
+    /// The largest such `k` is found with an integer square root and corrected by one where needed,
+    /// so the boundaries are exact. Working from `h` rather than squaring `time` fits times up to
+    /// about `2 * u64::MAX`, longer races are reported as an error rather than overflowing.
+    fn count_wins(&self) -> Result<u128> {
+        let (half, parity) = (self.time / 2, self.time % 2);
+        let best = half.checked_mul(half + parity).with_context(|| {
+            format!(
+                "a race of {} ms with a record of {} mm is too large to solve",
+                self.time, self.distance
+            )
+        })?;
+        let Some(margin) = best.checked_sub(self.distance).filter(|m| *m > 0) else {
+            return Ok(0);
+        };
+
+        // the largest `k` with `k * (k + p) < margin`, the square root is at most one too large.
+        let mut k = (margin - 1).isqrt();
+        if k * (k + parity) >= margin {
+            k -= 1;
+        }
+
+        // for an even time the offsets `0..=k` on both sides share the middle hold time.
+        Ok(if parity == 0 { 2 * k + 1 } else { 2 * (k + 1) })
     }
 }
 
-fn parse_input_2(input: &str) -> RResult {
-    let nums: Vec<u64> = input
-        .lines()
-        .map(|l| {
-            let nums: String = l.replace(|c: char| !c.is_numeric(), "");
-            nums.parse::<u64>().unwrap()
-        })
-        .collect();
-    RResult {
-        time: nums[0],
-        distance: nums[1],
+fn parse_line<'a>(line: Option<&'a str>, name: &str) -> Result<&'a str> {
+    let line = line.with_context(|| format!("missing the `{name}:` line"))?;
+    let (key, values) = record(line)?;
+    if key != name {
+        bail!("expected the `{name}:` line, found `{key}:`");
     }
+    Ok(values)
 }
 
-fn parse_input(input: &str) -> Vec<RResult> {
-    let lines = input.lines();
-    let num_lines: Vec<Vec<u64>> = lines
-        .map(|l| l.split(' ').filter_map(|s| s.parse::<u64>().ok()).collect())
-        .collect();
-    num_lines[0]
-        .iter()
-        .zip(&num_lines[1])
-        .map(|(t, d)| RResult {
-            time: *t,
-            distance: *d,
-        })
-        .collect()
+fn parse_input_2(input: &str) -> Result<RResult> {
+    let mut lines = input.lines().filter(|l| !l.trim().is_empty());
+    let mut read = |name: &str| -> Result<u128> {
+        let digits: String = parse_line(lines.next(), name)?
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        digits
+            .parse()
+            .with_context(|| format!("`{digits}` is not a valid {name}"))
+    };
+    Ok(RResult {
+        time: read("Time")?,
+        distance: read("Distance")?,
+    })
+}
+
+fn parse_input(input: &str) -> Result<Vec<RResult>> {
+    let mut lines = input.lines().filter(|l| !l.trim().is_empty());
+    let times: Vec<u128> = unsigned_ints(parse_line(lines.next(), "Time")?)?;
+    let distances: Vec<u128> = unsigned_ints(parse_line(lines.next(), "Distance")?)?;
+    if times.len() != distances.len() {
+        bail!(
+            "found {} times but {} distances",
+            times.len(),
+            distances.len()
+        );
+    }
+    Ok(times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, distance)| RResult { time, distance })
+        .collect())
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let res: usize = parse_input(input)
+pub fn part_one(input: &str) -> Result<u128> {
+    parse_input(input)?
         .iter()
-        .map(|x| x.get_possibilities().len())
-        .product();
-    Some(res.try_into().unwrap())
+        .map(RResult::count_wins)
+        .product()
 }
 
-// this is a bit slow
-pub fn part_two(input: &str) -> Option<u64> {
-    Some(
-        parse_input_2(input)
-            .get_possibilities()
-            .len()
-            .try_into()
-            .unwrap(),
-    )
+pub fn part_two(input: &str) -> Result<u128> {
+    parse_input_2(input)?.count_wins()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::property;
+
+    fn brute_force(race: &RResult) -> u128 {
+        (0..=race.time)
+            .filter(|n| n * (race.time - n) > race.distance)
+            .count() as u128
+    }
 
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(288));
+        assert_eq!(result.unwrap(), 288);
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(71503));
+        assert_eq!(result.unwrap(), 71503);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for time in 0..80 {
+            // every record up to one that cannot be beaten.
+            for distance in 0..=(time * time / 4 + 1) {
+                let race = RResult { time, distance };
+                assert_eq!(
+                    race.count_wins().unwrap(),
+                    brute_force(&race),
+                    "time {time}, distance {distance}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_brute_force_on_random_races() {
+        let race = |(time, distance): &(u64, u64)| RResult {
+            time: u128::from(*time),
+            distance: u128::from(*distance),
+        };
+        property::check_against(
+            "day 6 count_wins",
+            |rng| {
+                let time = rng.int(0..=5000);
+                // mostly records that can be beaten, and some that cannot.
+                let distance = rng.int(0..=time * time / 4 + 10);
+                (time as u64, distance as u64)
+            },
+            |input| brute_force(&race(input)),
+            |input| race(input).count_wins().unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_exact_boundaries_beyond_u64() {
+        let max = u128::from(u64::MAX);
+        for time in [max, max + 1, max + 12_345, 2 * max + 1] {
+            let best = (time / 2) * (time - time / 2);
+            for distance in [0, time, time * 1000, best - 1, best] {
+                let race = RResult { time, distance };
+                let wins = race.count_wins().unwrap();
+                let wins_at = |n: u128| n * (time - n) > distance;
+                if wins == 0 {
+                    assert!(!wins_at(time / 2), "time {time}, distance {distance}");
+                    continue;
+                }
+                // the winners are `first..=time - first`.
+                let first = (time + 1 - wins) / 2;
+                assert!(wins_at(first) && wins_at(time - first));
+                assert!(!wins_at(first - 1) && !wins_at(time - first + 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_concatenated_numbers_beyond_u64() {
+        let input = "Time: 1844674407 3709551615\nDistance: 10000000000000 0000000000000";
+        assert_eq!(part_two(input).unwrap(), 18446744073698709594);
+    }
+
+    #[test]
+    fn test_reports_malformed_input() {
+        assert!(part_one("Time: 7 15\nDistance: 9").is_err());
+        let err = part_two("Distance: 9\nTime: 7").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected the `Time:` line, found `Distance:`"
+        );
+        let err = part_two("Time: 73786976294838206464\nDistance: 1").unwrap_err();
+        assert!(err.to_string().contains("too large to solve"));
     }
 }
@@ -1,12 +1,16 @@
-use std::{cmp::Ordering, collections::HashMap, str::FromStr};
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt::Display,
+};
 
+use advent_of_code::parse::lines;
+use anyhow::{anyhow, ensure, Context, Result};
 use itertools::Itertools;
 
-advent_of_code::solution!(7);
+advent_of_code::solution!(7, explain = explain);
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
-enum Card {
-    Joker,
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum Card {
     Two,
     Three,
     Four,
@@ -22,8 +26,34 @@ enum Card {
     Ace,
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
-enum HandType {
+const CARDS: [Card; 13] = [
+    Card::Two,
+    Card::Three,
+    Card::Four,
+    Card::Five,
+    Card::Six,
+    Card::Seven,
+    Card::Eight,
+    Card::Nine,
+    Card::Ten,
+    Card::Jack,
+    Card::Queen,
+    Card::King,
+    Card::Ace,
+];
+
+impl Card {
+    fn label(self) -> char {
+        "23456789TJQKA".as_bytes()[self as usize] as char
+    }
+
+    fn from_label(c: char) -> Option<Card> {
+        CARDS.into_iter().find(|card| card.label() == c)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -33,256 +63,227 @@ enum HandType {
     FiveOfKind,
 }
 
-#[derive(Debug)]
-struct Hand {
-    cards: Vec<Card>,
-    bid: u32,
-    hand_type: HandType,
+impl HandType {
+    /// Classifies a hand by its two largest groups of equal cards, so it also works for hands
+    /// that are not five cards long.
+    fn from_groups(largest: usize, second: usize) -> HandType {
+        match (largest, second) {
+            (5.., _) => HandType::FiveOfKind,
+            (4, _) => HandType::FourOfKind,
+            (3, 2..) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfKind,
+            (2, 2) => HandType::TwoPair,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
 }
 
-fn calc_hand_type(cards: &[Card]) -> HandType {
-    let mut map: HashMap<&Card, u8> = HashMap::new();
-    cards.iter().for_each(|c| {
-        map.entry(c).and_modify(|v| *v += 1).or_insert(1);
-    });
-    let mut vec_values: Vec<u8> = map.values().copied().collect();
-    vec_values.sort();
-    vec_values.push(0);
-    vec_values.push(0);
-    vec_values.push(0);
-    vec_values.push(0); // pad with 4 zeroes
-    vec_values = vec_values.iter().take(5).copied().collect();
-    vec_values.sort();
-    let values: Option<(u8, u8, u8, u8, u8)> = vec_values.iter().take(5).copied().collect_tuple();
-    match values {
-        Some((0, 0, 0, 0, 5)) => HandType::FiveOfKind,
-        Some((0, 0, 0, 1, 4)) => HandType::FourOfKind,
-        Some((0, 0, 0, 2, 3)) => HandType::FullHouse,
-        Some((0, 0, 1, 1, 3)) => HandType::ThreeOfKind,
-        Some((0, 0, 1, 2, 2)) => HandType::TwoPair,
-        Some((_, _, _, _, 2)) => HandType::OnePair,
-        _ => HandType::HighCard,
+impl Display for HandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfKind => "three of a kind",
+            HandType::FullHouse => "full house",
+            HandType::FourOfKind => "four of a kind",
+            HandType::FiveOfKind => "five of a kind",
+        };
+        // pad like a string so the explanation table lines up.
+        f.pad(name)
     }
 }
 
-fn char_to_card(c: &char) -> Option<Card> {
-    match c {
-        'A' => Some(Card::Ace),
-        '2' => Some(Card::Two),
-        '3' => Some(Card::Three),
-        '4' => Some(Card::Four),
-        '5' => Some(Card::Five),
-        '6' => Some(Card::Six),
-        '7' => Some(Card::Seven),
-        '8' => Some(Card::Eight),
-        '9' => Some(Card::Nine),
-        'T' => Some(Card::Ten),
-        'J' => Some(Card::Jack),
-        'Q' => Some(Card::Queen),
-        'K' => Some(Card::King),
-        _ => None,
-    }
+/// How two hands of the same type are told apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Compare the cards in the order they were dealt, as the puzzle does.
+    DealtOrder,
+    /// Compare the largest groups first and the strongest cards within them, as in poker.
+    Grouped,
 }
 
-fn char_to_card_2(c: &char) -> Option<Card> {
-    match c {
-        'A' => Some(Card::Ace),
-        '2' => Some(Card::Two),
-        '3' => Some(Card::Three),
-        '4' => Some(Card::Four),
-        '5' => Some(Card::Five),
-        '6' => Some(Card::Six),
-        '7' => Some(Card::Seven),
-        '8' => Some(Card::Eight),
-        '9' => Some(Card::Nine),
-        'T' => Some(Card::Ten),
-        'J' => Some(Card::Joker),
-        'Q' => Some(Card::Queen),
-        'K' => Some(Card::King),
-        _ => None,
-    }
+/// The house rules a game of Camel Cards is played with.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    /// Every card, from the weakest to the strongest.
+    pub order: Vec<Card>,
+    /// Cards that stand in for whichever card makes the best hand.
+    pub wildcards: Vec<Card>,
+    pub hand_size: usize,
+    pub tie_break: TieBreak,
 }
 
-impl Hand {
-    fn new(cards: Vec<Card>, bid: u32) -> Self {
-        let hand_type = calc_hand_type(&cards);
-        Hand {
-            cards,
-            bid,
-            hand_type,
+impl Rules {
+    pub fn standard() -> Self {
+        Rules {
+            order: CARDS.to_vec(),
+            wildcards: vec![],
+            hand_size: 5,
+            tie_break: TieBreak::DealtOrder,
         }
     }
 
-    fn from_str_joker(input: &str) -> Self {
-        let (hand_str, bid_str) = input.split_once(' ').unwrap();
-        let cards: Vec<Card> = hand_str
-            .chars()
-            .map(|c| char_to_card_2(&c).unwrap())
-            .collect();
-        let bid = bid_str.parse::<u32>().unwrap();
-        let hand_type = calc_hand_type_2(&cards);
-        Hand {
-            cards,
-            bid,
-            hand_type,
+    /// `J` cards are jokers: wild, and the weakest card when breaking ties.
+    pub fn jokers() -> Self {
+        let mut order = CARDS.to_vec();
+        order.retain(|c| *c != Card::Jack);
+        order.insert(0, Card::Jack);
+        Rules {
+            order,
+            wildcards: vec![Card::Jack],
+            ..Rules::standard()
         }
     }
-}
 
-fn calc_hand_type_2(cards: &[Card]) -> HandType {
-    let mut primary_map: HashMap<&Card, u8> = HashMap::new();
-    cards.iter().for_each(|c| {
-        if c != &Card::Joker {
-            primary_map.entry(c).and_modify(|v| *v += 1).or_insert(1);
-        }
-    });
-    primary_map.insert(&Card::Joker, 0);
-    let biggest_key = primary_map
-        .iter()
-        .max_by(|a, b| a.1.cmp(b.1))
-        .map(|(k, _)| k)
-        .unwrap();
-    let joker_count = cards.iter().filter(|c| c == &&Card::Joker).count() as u8;
-    primary_map
-        .entry(biggest_key)
-        .and_modify(|x| *x += joker_count);
-    let mut vec_values: Vec<u8> = primary_map.values().copied().collect();
-    vec_values.sort();
-    vec_values.push(0);
-    vec_values.push(0);
-    vec_values.push(0);
-    vec_values.push(0); // pad with 4 zeroes
-    vec_values = vec_values.iter().take(5).copied().collect();
-    vec_values.sort();
-    let values: Option<(u8, u8, u8, u8, u8)> = vec_values.iter().take(5).copied().collect_tuple();
-    match values {
-        Some((0, 0, 0, 0, 5)) => HandType::FiveOfKind,
-        Some((0, 0, 0, 1, 4)) => HandType::FourOfKind,
-        Some((0, 0, 0, 2, 3)) => HandType::FullHouse,
-        Some((0, 0, 1, 1, 3)) => HandType::ThreeOfKind,
-        Some((0, 0, 1, 2, 2)) => HandType::TwoPair,
-        Some((_, _, _, _, 2)) => HandType::OnePair,
-        _ => HandType::HighCard,
+    fn strength(&self, card: Card) -> Option<usize> {
+        self.order.iter().position(|c| *c == card)
     }
-}
 
-fn compare_card_sets(left: &[Card], right: &[Card]) -> Ordering {
-    for i in 0..left.len() {
-        match left[i].cmp(&right[i]) {
-            Ordering::Equal => continue,
-            ord => return ord,
-        }
+    fn is_wild(&self, card: Card) -> bool {
+        self.wildcards.contains(&card)
     }
 
-    Ordering::Equal
-}
-
-impl PartialEq for Hand {
-    fn eq(&self, other: &Self) -> bool {
-        self.hand_type == other.hand_type
-            && compare_card_sets(&self.cards, &other.cards) == Ordering::Equal
+    /// Counts the non-wild cards of each kind, largest group first.
+    fn groups(&self, cards: &[Card]) -> Vec<(Card, usize)> {
+        cards
+            .iter()
+            .filter(|c| !self.is_wild(**c))
+            .counts()
+            .into_iter()
+            .map(|(c, n)| (*c, n))
+            .sorted_by_key(|(c, n)| Reverse((*n, self.strength(*c))))
+            .collect()
     }
-}
-
-impl Eq for Hand {}
 
-impl PartialOrd for Hand {
-    fn gt(&self, other: &Self) -> bool {
-        match self.hand_type.cmp(&other.hand_type) {
-            Ordering::Equal => compare_card_sets(&self.cards, &other.cards) == Ordering::Greater,
-            Ordering::Greater => true,
-            _ => false,
-        }
+    /// The hand with every wildcard replaced by the card it does best as: the card of the largest
+    /// group, the strongest one if groups tie, or the strongest card when every card is wild.
+    pub fn substitute(&self, cards: &[Card]) -> Vec<Card> {
+        let target = self.groups(cards).first().map(|(c, _)| *c).or_else(|| {
+            self.order
+                .iter()
+                .rev()
+                .find(|c| !self.is_wild(**c))
+                .copied()
+        });
+        cards
+            .iter()
+            .map(|c| match target {
+                Some(target) if self.is_wild(*c) => target,
+                _ => *c,
+            })
+            .collect()
     }
 
-    fn lt(&self, other: &Self) -> bool {
-        match self.hand_type.cmp(&other.hand_type) {
-            Ordering::Equal => compare_card_sets(&self.cards, &other.cards) == Ordering::Less,
-            Ordering::Less => true,
-            _ => false,
-        }
+    /// The best type a hand can be. Wildcards always do best joining the largest group.
+    pub fn classify(&self, cards: &[Card]) -> HandType {
+        let groups = self.groups(cards);
+        let wild = cards.len() - groups.iter().map(|(_, n)| n).sum::<usize>();
+        let largest = groups.first().map_or(0, |(_, n)| *n) + wild;
+        let second = groups.get(1).map_or(0, |(_, n)| *n);
+        HandType::from_groups(largest, second)
     }
 
-    fn ge(&self, other: &Self) -> bool {
-        self.gt(other) || self.eq(other)
+    /// The card strengths compared, in order, between hands of the same type.
+    fn tie_break_key(&self, cards: &[Card]) -> Vec<usize> {
+        let strength = |c: &Card| self.strength(*c).unwrap_or_default();
+        match self.tie_break {
+            TieBreak::DealtOrder => cards.iter().map(strength).collect(),
+            TieBreak::Grouped => {
+                let groups = self.groups(cards);
+                let wild = cards.iter().filter(|c| self.is_wild(**c)).map(strength);
+                groups
+                    .iter()
+                    .flat_map(|(c, n)| std::iter::repeat_n(strength(c), *n))
+                    .chain(wild.sorted().rev())
+                    .collect()
+            }
+        }
     }
 
-    fn le(&self, other: &Self) -> bool {
-        self.lt(other) || self.eq(other)
+    pub fn parse_hand(&self, line: &str) -> Result<Hand> {
+        let (hand_str, bid) = line
+            .split_once(' ')
+            .with_context(|| format!("expected `cards bid`, found `{line}`"))?;
+        let cards = hand_str
+            .chars()
+            .enumerate()
+            .map(|(ix, c)| {
+                Card::from_label(c)
+                    .filter(|card| self.strength(*card).is_some())
+                    .ok_or_else(|| anyhow!("column {}: `{c}` is not a card", ix + 1))
+            })
+            .collect::<Result<Vec<Card>>>()?;
+        ensure!(
+            cards.len() == self.hand_size,
+            "a hand has {} cards, found {}",
+            self.hand_size,
+            cards.len()
+        );
+        let bid = bid
+            .trim()
+            .parse()
+            .with_context(|| format!("`{}` is not a valid bid", bid.trim()))?;
+        Ok(Hand {
+            hand_type: self.classify(&cards),
+            key: self.tie_break_key(&cards),
+            substituted: self.substitute(&cards),
+            cards,
+            bid,
+        })
     }
+}
 
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/// A hand as scored by the [`Rules`] it was parsed with.
+#[derive(Debug)]
+pub struct Hand {
+    cards: Vec<Card>,
+    /// The cards with wildcards replaced, see [`Rules::substitute`].
+    substituted: Vec<Card>,
+    bid: u32,
+    hand_type: HandType,
+    key: Vec<usize>,
 }
 
-impl Ord for Hand {
-    fn clamp(self, min: Self, max: Self) -> Self
-    where
-        Self: Sized,
-        Self: PartialOrd,
-    {
-        if self < min {
-            min
-        } else if self > max {
-            max
-        } else {
-            self
-        }
+impl Hand {
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
     }
 
-    fn max(self, other: Self) -> Self
-    where
-        Self: Sized,
-    {
-        if self > other {
-            self
-        } else {
-            other
-        }
+    pub fn substituted(&self) -> &[Card] {
+        &self.substituted
     }
 
-    fn min(self, other: Self) -> Self
-    where
-        Self: Sized,
-    {
-        if self < other {
-            self
-        } else {
-            other
-        }
+    pub fn hand_type(&self) -> HandType {
+        self.hand_type
     }
+}
 
+fn labels(cards: &[Card]) -> String {
+    cards.iter().map(|c| c.label()).collect()
+}
+
+impl Ord for Hand {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.hand_type.cmp(&other.hand_type) {
-            Ordering::Equal => compare_card_sets(&self.cards, &other.cards),
-            o => o,
-        }
+        (self.hand_type, &self.key).cmp(&(other.hand_type, &other.key))
     }
 }
 
-#[derive(Debug)]
-struct Day6Error;
-
-impl FromStr for Hand {
-    type Err = Day6Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((hand_str, bid)) = s.split_once(' ') {
-            let cards: Vec<Card> = hand_str
-                .chars()
-                .map(|c| char_to_card(&c).unwrap())
-                .collect();
-            return match bid.parse::<u32>() {
-                Ok(b) => Ok(Hand::new(cards, b)),
-                Err(_) => Err(Day6Error),
-            };
-        }
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-        Err(Day6Error)
+impl PartialEq for Hand {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
+impl Eq for Hand {}
+
 fn hands_to_ranked_hands(hands: &mut [Hand]) -> Vec<(&Hand, u32)> {
     hands.sort();
     hands
@@ -295,42 +296,229 @@ fn hands_to_ranked_hands(hands: &mut [Hand]) -> Vec<(&Hand, u32)> {
         .collect()
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut hands: Vec<Hand> = input.lines().map(|l| l.parse::<Hand>().unwrap()).collect();
+/// The bid times the rank, which always fits in 64 bits.
+fn winnings(hand: &Hand, rank: u32) -> u64 {
+    u64::from(hand.bid) * u64::from(rank)
+}
+
+fn total_winnings(input: &str, rules: &Rules) -> Result<u64> {
+    let mut hands = lines(input, |l| rules.parse_hand(l))?;
     let ranked = hands_to_ranked_hands(&mut hands);
-    Some(ranked.iter().map(|(h, r)| h.bid * *r).sum())
+    ranked
+        .iter()
+        .try_fold(0_u64, |acc, (h, r)| acc.checked_add(winnings(h, *r)))
+        .context("the total winnings do not fit in 64 bits")
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut hands: Vec<Hand> = input.lines().map(Hand::from_str_joker).collect();
+/// Lists the hands from weakest to strongest under the given rules.
+fn ranked_table(input: &str, rules: &Rules) -> Result<String> {
+    let mut hands = lines(input, |l| rules.parse_hand(l))?;
     let ranked = hands_to_ranked_hands(&mut hands);
-    Some(ranked.iter().map(|(h, r)| h.bid * *r).sum())
+    let size = rules.hand_size.max(4);
+    let mut res = format!(
+        "{:>5}  {:size$}  {:size$}  {:15}  {:>6}  {:>9}\n",
+        "rank", "hand", "as", "type", "bid", "winnings"
+    );
+    for (hand, rank) in ranked {
+        res.push_str(&format!(
+            "{rank:>5}  {:size$}  {:size$}  {:15}  {:>6}  {:>9}\n",
+            labels(&hand.cards),
+            labels(&hand.substituted),
+            hand.hand_type,
+            hand.bid,
+            winnings(hand, rank)
+        ));
+    }
+    Ok(res)
+}
+
+/// Prints the ranked hands for both parts, see `cargo solve 7 --explain`.
+pub fn explain(input: &str) -> Result<String> {
+    Ok(format!(
+        "Part 1\n{}\nPart 2 (jokers)\n{}",
+        ranked_table(input, &Rules::standard())?,
+        ranked_table(input, &Rules::jokers())?
+    ))
+}
+
+pub fn part_one(input: &str) -> Result<u64> {
+    total_winnings(input, &Rules::standard())
+}
+
+pub fn part_two(input: &str) -> Result<u64> {
+    total_winnings(input, &Rules::jokers())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn cards(labels: &str) -> Vec<Card> {
+        labels
+            .chars()
+            .map(|c| Card::from_label(c).unwrap())
+            .collect()
+    }
+
+    /// Tries every card in place of each wildcard. Cards that are not in the hand all do the same,
+    /// so one of them is enough.
+    fn brute_force_type(rules: &Rules, cards: &[Card]) -> HandType {
+        let tame = Rules {
+            wildcards: vec![],
+            ..rules.clone()
+        };
+        let mut candidates = cards
+            .iter()
+            .filter(|c| !rules.is_wild(**c))
+            .copied()
+            .collect_vec();
+        candidates.extend(CARDS.into_iter().find(|c| !cards.contains(c)));
+        cards
+            .iter()
+            .map(|c| {
+                if rules.is_wild(*c) {
+                    candidates.clone()
+                } else {
+                    vec![*c]
+                }
+            })
+            .multi_cartesian_product()
+            .map(|hand| tame.classify(&hand))
+            .max()
+            .unwrap()
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(6440));
+        assert_eq!(result.unwrap(), 6440);
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(5905));
+        assert_eq!(result.unwrap(), 5905);
     }
 
     #[test]
     fn test_compare() {
         let c1 = Card::Two;
         let c2 = Card::Three;
-        assert!(&c1 < &c2);
+        assert!(c1 < c2);
         let mut input = vec![&c1, &c2];
         input.sort();
         let expected = vec![&c1, &c2];
         assert_eq!(input, expected);
     }
+
+    #[test]
+    fn test_classifies_every_shape() {
+        let rules = Rules::standard();
+        let shapes = [
+            ("23456", HandType::HighCard),
+            ("22345", HandType::OnePair),
+            ("22334", HandType::TwoPair),
+            ("22234", HandType::ThreeOfKind),
+            ("22233", HandType::FullHouse),
+            ("22223", HandType::FourOfKind),
+            ("22222", HandType::FiveOfKind),
+        ];
+        for (hand, expected) in shapes {
+            // the type cannot depend on the order the cards were dealt in.
+            for perm in cards(hand).into_iter().permutations(5) {
+                assert_eq!(rules.classify(&perm), expected, "{hand}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_wildcards_match_brute_force() {
+        let rules = Rules::jokers();
+        // every hand over a few cards, with any number of jokers and every shape around them.
+        for hand in (0..5).map(|_| cards("2345J")).multi_cartesian_product() {
+            assert_eq!(
+                rules.classify(&hand),
+                brute_force_type(&rules, &hand),
+                "{}",
+                hand.iter().map(|c| c.label()).collect::<String>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_substitution_makes_the_best_type() {
+        let rules = Rules::jokers();
+        let tame = Rules {
+            wildcards: vec![],
+            ..rules.clone()
+        };
+        for hand in (0..5).map(|_| cards("2345J")).multi_cartesian_product() {
+            let substituted = rules.substitute(&hand);
+            assert!(substituted.iter().all(|c| !rules.is_wild(*c)));
+            assert_eq!(tame.classify(&substituted), rules.classify(&hand));
+        }
+    }
+
+    #[test]
+    fn test_substitutes_jokers() {
+        let rules = Rules::jokers();
+        for (hand, expected) in [
+            ("KTJJT", "KTTTT"),
+            ("QQQJA", "QQQQA"),
+            ("T55J5", "T5555"),
+            // with two pairs to choose from, the stronger pair gets the joker.
+            ("KK22J", "KK22K"),
+            ("JJJJJ", "AAAAA"),
+            ("32T3K", "32T3K"),
+        ] {
+            let parsed = rules.parse_hand(&format!("{hand} 1")).unwrap();
+            assert_eq!(labels(parsed.substituted()), expected);
+        }
+    }
+
+    #[test]
+    fn test_explains_rankings() {
+        let report = explain(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let part_two = report.split("Part 2").nth(1).unwrap();
+        let rows: Vec<&str> = part_two.lines().skip(2).collect();
+        assert_eq!(rows.len(), 5);
+        // KTJJT is the strongest hand with jokers and wins 5 * 220.
+        assert_eq!(
+            rows[4].split_whitespace().collect::<Vec<_>>(),
+            ["5", "KTJJT", "KTTTT", "four", "of", "a", "kind", "220", "1100"]
+        );
+    }
+
+    #[test]
+    fn test_jokers_are_weakest_when_breaking_ties() {
+        let rules = Rules::jokers();
+        let joker = rules.parse_hand("JKKK2 1").unwrap();
+        let queen = rules.parse_hand("QQQQ2 1").unwrap();
+        assert_eq!(joker.hand_type(), HandType::FourOfKind);
+        assert!(joker < queen);
+    }
+
+    #[test]
+    fn test_house_rules() {
+        // twos are wild too, hands are three cards long and ties are settled poker-style.
+        let rules = Rules {
+            wildcards: vec![Card::Two, Card::Jack],
+            hand_size: 3,
+            tie_break: TieBreak::Grouped,
+            ..Rules::standard()
+        };
+        assert_eq!(rules.classify(&cards("2J9")), HandType::ThreeOfKind);
+        // dealt in order the ace would win, grouped the higher pair does.
+        let low_pair = rules.parse_hand("A55 1").unwrap();
+        let high_pair = rules.parse_hand("66K 1").unwrap();
+        assert_eq!(low_pair.hand_type(), HandType::OnePair);
+        assert!(low_pair < high_pair);
+        assert!(rules.parse_hand("23456 1").is_err());
+    }
+
+    #[test]
+    fn test_reports_bad_cards() {
+        let err = part_one("32T3K 765\nT55X5 684").unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 2: column 4: `X` is not a card");
+    }
 }
@@ -1,11 +1,10 @@
 use advent_of_code::lcm;
+use anyhow::{bail, ensure, Context, Result};
+use itertools::Itertools;
 use rayon::prelude::*;
-use std::{
-    collections::{BTreeMap, HashMap},
-    hash::Hash,
-};
+use std::{collections::HashMap, fmt::Write, fs, path::Path};
 
-advent_of_code::solution!(8);
+advent_of_code::solution!(8, explain = explain);
 
 enum Direction {
     Left,
@@ -20,162 +19,287 @@ fn char_to_dir(c: char) -> Option<Direction> {
     }
 }
 
-#[derive(PartialOrd, Ord)]
-struct NodeKey {
-    key: String,
-    is_start: bool,
-    is_end: bool,
+struct Node {
+    name: String,
+    left: usize,
+    right: usize,
 }
 
-impl PartialEq for NodeKey {
-    fn eq(&self, other: &Self) -> bool {
-        self.key == other.key
+impl Node {
+    fn is_start(&self) -> bool {
+        self.name.ends_with('A')
     }
-}
 
-impl Hash for NodeKey {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.key.hash(state);
+    fn is_end(&self) -> bool {
+        self.name.ends_with('Z')
     }
+}
 
-    fn hash_slice<H: std::hash::Hasher>(data: &[Self], state: &mut H)
-    where
-        Self: Sized,
-    {
-        data.hash(state);
-    }
+/// The network, with every node referred to by its position in `nodes`.
+struct Network {
+    nodes: Vec<Node>,
+    ids: HashMap<String, usize>,
+    instructions: Vec<Direction>,
 }
 
-impl Eq for NodeKey {}
+/// How the walk from a start node settles into a loop, in terms of (node, instruction) states.
+struct Cycle {
+    start: usize,
+    /// Steps taken before the walk first reaches a state that repeats.
+    offset: usize,
+    length: usize,
+    /// The steps within the first `offset + length` steps at which an end node is reached.
+    end_hits: Vec<(usize, usize)>,
+}
 
-struct Puzzle {
-    nodes: HashMap<NodeKey, (String, String)>,
-    instructions: Vec<Direction>,
+impl Cycle {
+    /// The LCM shortcut assumes a walk is at an end node exactly every `n` steps, where `n` is the
+    /// number of steps to the first end node, so the walks all line up at the LCM of those counts.
+    fn fits_lcm(&self) -> bool {
+        let Some(&(first, _)) = self.end_hits.first() else {
+            return false;
+        };
+        let multiples = (1..)
+            .map(|k| k * first)
+            .take_while(|step| *step < self.offset + self.length);
+        first > 0
+            && self.length.is_multiple_of(first)
+            && self.end_hits.iter().map(|(step, _)| *step).eq(multiples)
+    }
 }
 
-impl Puzzle {
-    fn steps_to_zzz(&self) -> u64 {
-        let mut dir_cursor = 0;
-        let mut steps: u64 = 0;
-        let mut curr_node = &String::from("AAA");
-        while curr_node != &String::from("ZZZ") {
+impl Network {
+    fn id(&self, name: &str) -> Result<usize> {
+        self.ids
+            .get(name)
+            .copied()
+            .with_context(|| format!("node `{name}` is not in the network"))
+    }
+
+    fn step(&self, node: usize, cursor: usize) -> usize {
+        match self.instructions[cursor % self.instructions.len()] {
+            Direction::Left => self.nodes[node].left,
+            Direction::Right => self.nodes[node].right,
+        }
+    }
+
+    fn steps_until(&self, start: usize, is_goal: impl Fn(usize) -> bool) -> Result<usize> {
+        let mut node = start;
+        let mut steps = 0;
+        // once every (node, instruction) state has been seen the walk is going round in circles.
+        let limit = self.nodes.len() * self.instructions.len();
+        while !is_goal(node) {
+            ensure!(
+                steps <= limit,
+                "the walk from `{}` never reaches its goal",
+                self.nodes[start].name
+            );
+            node = self.step(node, steps);
             steps += 1;
-            let next_dir = &self.instructions[dir_cursor];
-            let choices = self
-                .nodes
-                .get(&NodeKey {
-                    key: curr_node.to_string(),
-                    is_start: false,
-                    is_end: false,
-                })
-                .unwrap();
-            match next_dir {
-                Direction::Left => curr_node = &choices.0,
-                Direction::Right => curr_node = &choices.1,
+        }
+        Ok(steps)
+    }
+
+    fn starts(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|ix| self.nodes[*ix].is_start())
+            .collect()
+    }
+
+    fn steps_to_zzz(&self) -> Result<u64> {
+        let end = self.id("ZZZ")?;
+        let steps = self.steps_until(self.id("AAA")?, |ix| ix == end)?;
+        Ok(steps as u64)
+    }
+
+    fn steps_to_z(&self) -> Result<usize> {
+        let steps_iter: Vec<usize> = self
+            .starts()
+            .par_iter()
+            .map(|start| self.steps_until(*start, |ix| self.nodes[ix].is_end()))
+            .collect::<Result<_>>()?;
+        if steps_iter.is_empty() {
+            bail!("there are no start nodes ending in `A`");
+        }
+        lcm(&steps_iter).context("the steps until every walk ends in `Z` do not fit in a usize")
+    }
+
+    fn cycle(&self, start: usize) -> Cycle {
+        let instructions = self.instructions.len();
+        let mut seen: Vec<Option<usize>> = vec![None; self.nodes.len() * instructions];
+        let mut end_hits = vec![];
+        let mut node = start;
+        let mut steps = 0;
+        loop {
+            let state = node * instructions + steps % instructions;
+            if let Some(first) = seen[state] {
+                return Cycle {
+                    start,
+                    offset: first,
+                    length: steps - first,
+                    end_hits,
+                };
             }
-            dir_cursor += 1;
-            if dir_cursor == self.instructions.len() {
-                dir_cursor = 0;
+            seen[state] = Some(steps);
+            if self.nodes[node].is_end() {
+                end_hits.push((steps, node));
             }
+            node = self.step(node, steps);
+            steps += 1;
         }
-        steps
     }
 
-    fn steps_to_z(&self) -> usize {
-        let start_keys: Vec<&NodeKey> = self.nodes.keys().filter(|x| x.is_start).collect();
-        let mut key_index: BTreeMap<String, &NodeKey> = BTreeMap::new();
-        for x in &self.nodes {
-            let k = x.0;
-            key_index.insert(k.key.to_string(), k);
+    /// Draws the network as a Graphviz digraph, with start nodes green and end nodes red.
+    fn to_dot(&self) -> String {
+        let mut res = String::from("digraph network {\n");
+        for node in &self.nodes {
+            let colour = match (node.is_start(), node.is_end()) {
+                (true, _) => " [style=filled, fillcolor=palegreen]",
+                (_, true) => " [style=filled, fillcolor=salmon]",
+                _ => "",
+            };
+            let _ = writeln!(res, "  \"{}\"{colour};", node.name);
         }
-        let steps_iter: Vec<usize> = start_keys
-            .par_iter()
-            .map(|k| {
-                let mut dir_cursor = 0;
-                let mut steps: u64 = 0;
-                let mut curr_node = k;
-                while !curr_node.is_end {
-                    steps += 1;
-                    let next_dir = &self.instructions[dir_cursor];
-                    let choices = self.nodes.get(curr_node).unwrap();
-                    match next_dir {
-                        Direction::Left => curr_node = key_index.get(&choices.0).unwrap(),
-                        Direction::Right => curr_node = key_index.get(&choices.1).unwrap(),
-                    }
-                    dir_cursor += 1;
-                    if dir_cursor == self.instructions.len() {
-                        dir_cursor = 0;
-                    }
-                }
-                steps as usize
-            })
-            .collect();
-        lcm(&steps_iter)
+        for node in &self.nodes {
+            for (label, next) in [("L", node.left), ("R", node.right)] {
+                let _ = writeln!(
+                    res,
+                    "  \"{}\" -> \"{}\" [label={label}];",
+                    node.name, self.nodes[next].name
+                );
+            }
+        }
+        res.push_str("}\n");
+        res
     }
 }
 
-fn parse_input(input: &str) -> Puzzle {
+fn parse_node(line: &str) -> Result<(&str, &str, &str)> {
+    let parsed = line.split_once('=').and_then(|(name, links)| {
+        let links = links.trim().strip_prefix('(')?.strip_suffix(')')?;
+        let (left, right) = links.split_once(',')?;
+        Some((name.trim(), left.trim(), right.trim()))
+    });
+    parsed.with_context(|| format!("expected `AAA = (BBB, CCC)`, found `{line}`"))
+}
+
+fn parse_input(input: &str) -> Result<Network> {
     let mut lines = input.lines();
-    let direction_line = lines.next().unwrap();
+    let direction_line = lines.next().context("input is empty")?;
     let instructions: Vec<Direction> = direction_line.chars().filter_map(char_to_dir).collect();
-    // blank line
-    lines.next();
-
-    let mut nodes: HashMap<NodeKey, (String, String)> = HashMap::new();
-    for l in lines {
-        let split: Vec<String> = l
-            .split(' ')
-            .filter_map(|s| {
-                let tmp = s.replace(|c: char| !c.is_alphanumeric(), "");
-                match tmp.is_empty() {
-                    true => None,
-                    false => Some(tmp),
-                }
-            })
-            .collect();
-        if split.len() == 3 {
-            let key = split[0].as_str();
-            let l = split[1].as_str();
-            let r = split[2].as_str();
-            let is_start = key.ends_with('A');
-            let is_end = key.ends_with('Z');
-            nodes.insert(
-                NodeKey {
-                    key: String::from(key),
-                    is_start,
-                    is_end,
-                },
-                (String::from(l), String::from(r)),
-            );
+    if instructions.is_empty() {
+        bail!("line 1: expected a sequence of `L`/`R` instructions");
+    }
+
+    let links: Vec<(usize, (&str, &str, &str))> = lines
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(ix, l)| {
+            Ok((
+                ix + 2,
+                parse_node(l).with_context(|| format!("line {}", ix + 2))?,
+            ))
+        })
+        .collect::<Result<_>>()?;
+
+    // every node gets its index first, so links can point forwards.
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    for (line, (name, _, _)) in &links {
+        if ids.insert(name.to_string(), ids.len()).is_some() {
+            bail!("line {line}: node `{name}` is listed twice");
         }
     }
 
-    Puzzle {
-        nodes,
+    let mut network = Network {
+        nodes: Vec::with_capacity(links.len()),
+        ids,
         instructions,
+    };
+    for (line, (name, left, right)) in links {
+        let ctx = || format!("line {line}");
+        let left = network.id(left).with_context(ctx)?;
+        let right = network.id(right).with_context(ctx)?;
+        network.nodes.push(Node {
+            name: name.to_string(),
+            left,
+            right,
+        });
     }
+
+    Ok(network)
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let puzzle = parse_input(input);
-    Some(puzzle.steps_to_zzz())
+/// Reports how the walk from each start node loops, and writes the network to `data/graphs/08.dot`.
+pub fn explain(input: &str) -> Result<String> {
+    let network = parse_input(input)?;
+    let mut res = explain_cycles(&network);
+
+    let path = Path::new("data").join("graphs").join(format!("{DAY}.dot"));
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, network.to_dot())
+        .with_context(|| format!("could not write {}", path.display()))?;
+    let _ = write!(res, "\nWrote the network to {}", path.display());
+    Ok(res)
 }
 
-pub fn part_two(input: &str) -> Option<usize> {
-    let puzzle = parse_input(input);
-    Some(puzzle.steps_to_z())
+fn explain_cycles(network: &Network) -> String {
+    let mut res = String::new();
+    let cycles = network
+        .starts()
+        .into_iter()
+        .map(|start| network.cycle(start))
+        .collect_vec();
+    for cycle in &cycles {
+        let hits = cycle
+            .end_hits
+            .iter()
+            .map(|(step, node)| format!("{} at step {step}", network.nodes[*node].name))
+            .join(", ");
+        let _ = writeln!(
+            res,
+            "{}: loops every {} steps after {} steps, reaching {}{}",
+            network.nodes[cycle.start].name,
+            cycle.length,
+            cycle.offset,
+            if hits.is_empty() {
+                "no end node"
+            } else {
+                &hits
+            },
+            if cycle.fits_lcm() {
+                ""
+            } else {
+                " (breaks the LCM shortcut)"
+            }
+        );
+    }
+    let fits = cycles.iter().all(Cycle::fits_lcm);
+    let _ = write!(
+        res,
+        "The LCM shortcut {} for this network.",
+        if fits { "holds" } else { "does not hold" }
+    );
+    res
+}
+
+pub fn part_one(input: &str) -> Result<u64> {
+    let puzzle = parse_input(input)?;
+    puzzle.steps_to_zzz()
+}
+
+pub fn part_two(input: &str) -> Result<usize> {
+    let puzzle = parse_input(input)?;
+    puzzle.steps_to_z()
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use super::*;
 
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(6));
+        assert_eq!(result.unwrap(), 6);
     }
 
     #[test]
@@ -183,41 +307,80 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file_part(
             "examples", DAY, 2,
         ));
-        assert_eq!(result, Some(6));
+        assert_eq!(result.unwrap(), 6);
     }
 
     #[test]
     fn compare_nodes() {
-        let a = NodeKey {
-            is_end: false,
-            is_start: true,
-            key: String::from("AAA"),
-        };
-        let b_key: &String = &"AAA".to_string();
-        let b = NodeKey {
-            is_end: true,
-            is_start: false,
-            key: b_key.to_string(),
-        };
-        assert!(a.eq(&b));
+        let network = parse_input("L\n\nAAA = (AAA, ZZZ)\nZZZ = (AAA, ZZZ)").unwrap();
+        // both links to `AAA` are interned to the same node.
+        assert_eq!(network.nodes[0].left, network.nodes[1].left);
+        assert_eq!(network.id("AAA").unwrap(), 0);
     }
 
     #[test]
     fn map_test() {
-        let a = NodeKey {
-            is_end: false,
-            is_start: true,
-            key: String::from("AAA"),
-        };
-        let b_key: &String = &"AAA".to_string();
-        let b = NodeKey {
-            is_end: true,
-            is_start: false,
-            key: b_key.to_string(),
-        };
-        let mut map: HashMap<NodeKey, u8> = HashMap::new();
-        map.insert(a, 1);
-        let entry = map.get(&b);
-        assert!(entry.is_some());
+        let network = parse_input("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)").unwrap();
+        let b = network.id("BBB").unwrap();
+        assert_eq!(network.nodes[b].name, "BBB");
+        assert!(network.id("CCC").is_err());
+    }
+
+    #[test]
+    fn test_reports_unknown_nodes() {
+        let err = parse_input("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)")
+            .err()
+            .unwrap();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 3: node `BBB` is not in the network"
+        );
+        let err = part_one("L\n\nAAA = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the walk from `AAA` never reaches its goal"
+        );
+    }
+
+    #[test]
+    fn test_finds_cycles() {
+        let network = parse_input(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ))
+        .unwrap();
+        let report = explain_cycles(&network);
+        assert_eq!(
+            report,
+            "11A: loops every 2 steps after 1 steps, reaching 11Z at step 2\n\
+            22A: loops every 6 steps after 1 steps, reaching 22Z at step 3, 22Z at step 6\n\
+            The LCM shortcut holds for this network."
+        );
+    }
+
+    #[test]
+    fn test_finds_cycles_that_break_the_lcm_shortcut() {
+        let network = parse_input(
+            "L\n\n11A = (11Z, 11Z)\n11Z = (11B, 11B)\n11B = (11C, 11C)\n11C = (11Z, 11Z)",
+        )
+        .unwrap();
+        let report = explain_cycles(&network);
+        assert_eq!(
+            report,
+            "11A: loops every 3 steps after 1 steps, reaching 11Z at step 1 \
+            (breaks the LCM shortcut)\n\
+            The LCM shortcut does not hold for this network."
+        );
+    }
+
+    #[test]
+    fn test_exports_dot() {
+        let network =
+            parse_input("LR\n\nAAA = (BBB, ZZZ)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)").unwrap();
+        let dot = network.to_dot();
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.contains("  \"AAA\" [style=filled, fillcolor=palegreen];\n"));
+        assert!(dot.contains("  \"ZZZ\" [style=filled, fillcolor=salmon];\n"));
+        assert!(dot.contains("  \"AAA\" -> \"ZZZ\" [label=R];\n"));
+        assert_eq!(dot.matches("->").count(), 6);
     }
 }
@@ -1,69 +1,192 @@
 advent_of_code::solution!(9);
 
-use hashbrown::HashSet;
+use advent_of_code::parse::{lines, signed_ints};
+use anyhow::{bail, ensure, Context, Result};
 
-fn unique(xs: &[i64]) -> Option<i64> {
-    if xs.iter().copied().collect::<HashSet<i64>>().len() == 1 {
-        Some(xs[0])
-    } else {
-        None
-    }
+/// A sequence that follows a polynomial, described by the first value of every row of its
+/// difference table down to the first constant row.
+pub struct Sequence {
+    /// `firsts[j]` is the first value of the `j`th row of differences.
+    firsts: Vec<i128>,
+    len: usize,
 }
 
-fn get_next_in_seq(seq: Vec<i64>) -> i64 {
-    let mut diffs: Vec<i64> = Vec::new();
-    for windows in seq.windows(2) {
-        let diff = windows[1] - windows[0];
-        diffs.push(diff);
+impl Sequence {
+    /// Builds the difference table of `values`. Fails unless a row of at least two equal values
+    /// is reached, as otherwise the values do not pin down a polynomial.
+    pub fn new(values: &[i64]) -> Result<Self> {
+        ensure!(!values.is_empty(), "the sequence is empty");
+        let mut row: Vec<i128> = values.iter().map(|v| *v as i128).collect();
+        let mut firsts = vec![];
+        loop {
+            firsts.push(row[0]);
+            if row.iter().all(|v| *v == row[0]) {
+                if row.len() < 2 {
+                    bail!(
+                        "the differences of the sequence never become constant, \
+                        it needs more than {} values",
+                        values.len()
+                    );
+                }
+                return Ok(Sequence {
+                    firsts,
+                    len: values.len(),
+                });
+            }
+            row = row
+                .windows(2)
+                .map(|w| w[1].checked_sub(w[0]))
+                .collect::<Option<Vec<i128>>>()
+                .context("the differences of the sequence do not fit in 128 bits")?;
+        }
     }
-    match unique(&diffs) {
-        Some(x) => seq.last().unwrap() + x,
-        None => seq.last().unwrap() + get_next_in_seq(diffs),
+
+    /// The degree of the polynomial the sequence follows.
+    pub fn degree(&self) -> usize {
+        self.firsts.len() - 1
     }
-}
 
-fn get_next_in_seq_back(seq: Vec<i64>) -> i64 {
-    let mut diffs: Vec<i64> = Vec::new();
-    for windows in seq.windows(2) {
-        let diff = windows[1] - windows[0];
-        diffs.push(diff);
+    /// The value at position `x`, where the first value is at position 0.
+    ///
+    /// Newton's forward difference formula gives `f(x) = sum(C(x, j) * firsts[j])`. It also holds
+    /// for negative `x`, where `C(x, j)` is the generalised binomial coefficient.
+    pub fn at(&self, x: i128) -> Result<i128> {
+        let overflow = || format!("the value at position {x} does not fit in 128 bits");
+        let mut binomial: i128 = 1;
+        let mut res: i128 = 0;
+        for (j, first) in self.firsts.iter().enumerate() {
+            if j > 0 {
+                // `C(x, j) = C(x, j - 1) * (x - j + 1) / j`, and the division is always exact.
+                binomial = binomial
+                    .checked_mul(x - j as i128 + 1)
+                    .with_context(overflow)?
+                    / j as i128;
+            }
+            let term = binomial.checked_mul(*first).with_context(overflow)?;
+            res = res.checked_add(term).with_context(overflow)?;
+        }
+        Ok(res)
     }
-    match unique(&diffs) {
-        Some(x) => seq.first().unwrap() - x,
-        None => seq.first().unwrap() - get_next_in_seq_back(diffs),
+
+    /// The value `steps` after the last one.
+    pub fn forward(&self, steps: u64) -> Result<i128> {
+        self.at(self.len as i128 - 1 + steps as i128)
+    }
+
+    /// The value `steps` before the first one.
+    pub fn backward(&self, steps: u64) -> Result<i128> {
+        self.at(-(steps as i128))
     }
 }
 
-pub fn part_one(input: &str) -> Option<i64> {
-    let res = input
-        .lines()
-        .map(|l| get_next_in_seq(l.split(' ').map(|s| s.parse::<i64>().unwrap()).collect()))
-        .sum();
-    Some(res)
+fn parse_sequences(input: &str) -> Result<Vec<Sequence>> {
+    lines(input, |l| Sequence::new(&signed_ints(l)?))
 }
 
-pub fn part_two(input: &str) -> Option<i64> {
-    let res: Vec<i64> = input
-        .lines()
-        .map(|l| get_next_in_seq_back(l.split(' ').map(|s| s.parse::<i64>().unwrap()).collect()))
-        .collect();
-    let res = res.iter().sum();
-    Some(res)
+pub fn part_one(input: &str) -> Result<i128> {
+    parse_sequences(input)?.iter().map(|s| s.forward(1)).sum()
+}
+
+pub fn part_two(input: &str) -> Result<i128> {
+    parse_sequences(input)?.iter().map(|s| s.backward(1)).sum()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::property;
 
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(114));
+        assert_eq!(result.unwrap(), 114);
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(2));
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    /// The next and the previous value, by extending every row of differences down to zeros.
+    fn extrapolate(values: &[i128]) -> (i128, i128) {
+        if values.iter().all(|v| *v == 0) {
+            return (0, 0);
+        }
+        let diffs: Vec<i128> = values.windows(2).map(|w| w[1] - w[0]).collect();
+        let (next, previous) = extrapolate(&diffs);
+        (values[values.len() - 1] + next, values[0] - previous)
+    }
+
+    #[test]
+    fn test_matches_recursive_differences() {
+        // `coefficients` of a polynomial, and how many more values than needed to pin it down.
+        let values = |(coefficients, extra): &(Vec<i64>, usize)| -> Vec<i64> {
+            (0..coefficients.len() + 2 + extra)
+                .map(|x| {
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, c| acc * x as i64 + c)
+                })
+                .collect()
+        };
+        property::check_against(
+            "day 9 extrapolation",
+            |rng| {
+                let coefficients = rng.vec(0..=5, |r| r.int(-20..=20));
+                (coefficients, rng.below(8))
+            },
+            |input| {
+                let values: Vec<i128> = values(input).iter().map(|v| *v as i128).collect();
+                extrapolate(&values)
+            },
+            |input| {
+                let seq = Sequence::new(&values(input)).unwrap();
+                (seq.forward(1).unwrap(), seq.backward(1).unwrap())
+            },
+        );
+    }
+
+    #[test]
+    fn test_reports_degree() {
+        let degrees: Vec<usize> =
+            parse_sequences(&advent_of_code::template::read_file("examples", DAY))
+                .unwrap()
+                .iter()
+                .map(Sequence::degree)
+                .collect();
+        assert_eq!(degrees, vec![1, 2, 3]);
+        assert_eq!(Sequence::new(&[7, 7]).unwrap().degree(), 0);
+    }
+
+    #[test]
+    fn test_extrapolates_many_steps() {
+        let cube = |x: i128| x * x * x - 4 * x + 2;
+        let values: Vec<i64> = (0..6).map(|x| cube(x) as i64).collect();
+        let seq = Sequence::new(&values).unwrap();
+        assert_eq!(seq.degree(), 3);
+        for steps in [0, 1, 2, 10, 1000] {
+            assert_eq!(seq.forward(steps).unwrap(), cube(5 + steps as i128));
+            assert_eq!(seq.backward(steps).unwrap(), cube(-(steps as i128)));
+        }
+    }
+
+    #[test]
+    fn test_rejects_sequences_without_a_constant_row() {
+        let err = Sequence::new(&[1, 2, 4, 8, 16]).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "the differences of the sequence never become constant, it needs more than 5 values"
+        );
+        let err = part_one("0 3 6\n1 2 4 8 16").unwrap_err();
+        assert!(format!("{err:#}").starts_with("line 2: "));
+        assert!(Sequence::new(&[42]).is_err());
+    }
+
+    #[test]
+    fn test_reports_overflow() {
+        let seq = Sequence::new(&[0, 1, 4, 9]).unwrap();
+        assert!(seq.forward(u64::MAX).is_err());
     }
 }
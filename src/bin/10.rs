@@ -1,11 +1,10 @@
-// NOTE: Not my own work - this is copied from the github link below in an attempt to understand how to solve this problem.
-// I do not think this attempt was successful, I still do not properly understand it.
+// NOTE: The tile parsing is not my own work - it was copied from the github link below in an attempt to understand how to solve this problem.
+// The original heap-based search has since been replaced by walking the loop directly.
 
 // link: https://github.com/agausmann/puzzles/blob/52b3d7ca715d8b51d9ba3d9a595a496a449d10e9/adventofcode/aoc2023/src/bin/day10.rs
 
-use std::{cmp::Reverse, collections::BinaryHeap};
-
-use hashbrown::HashSet;
+use advent_of_code::viz::{self, Colour, Grid};
+use anyhow::{anyhow, bail, Context, Result};
 use itertools::Itertools;
 
 advent_of_code::solution!(10);
@@ -61,73 +60,236 @@ impl From<char> for MapTile {
     }
 }
 
-fn get_map(input: &str) -> (Vec<Vec<MapTile>>, (usize, usize)) {
-    let mut start: Option<(usize, usize)> = None;
-    let mut map = input
-        .lines()
-        .enumerate()
-        .map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .map(|(x, c)| {
-                    if c == 'S' {
-                        // horrid mutation of a variable in a map, just to avoid having to find the start later
-                        // this has to be y,x because of the way I'm constructing the grid (ie. sideways???)
-                        start = Some((y, x));
-                    }
-                    MapTile::from(c)
-                })
-                .collect_vec()
-        })
-        .collect_vec();
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
 
-    let start = start.unwrap();
-    map[start.0][start.1].north = start.0 > 0 && map[start.0 - 1][start.1].south;
-    map[start.0][start.1].south = start.0 < map.len() - 1 && map[start.0 + 1][start.1].north;
-    map[start.0][start.1].west = start.1 > 0 && map[start.0][start.1 - 1].east;
-    map[start.0][start.1].east = start.1 < map[start.0].len() - 1 && map[start.0][start.1 + 1].west;
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::East,
+    Direction::West,
+];
 
-    (map, start)
+impl Direction {
+    fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+
+    /// The neighbouring position in this direction, if it is on the map.
+    fn step(self, (i, j): (usize, usize), map: &[Vec<MapTile>]) -> Option<(usize, usize)> {
+        let next = match self {
+            Direction::North => (i.checked_sub(1)?, j),
+            Direction::South => (i + 1, j),
+            Direction::East => (i, j + 1),
+            Direction::West => (i, j.checked_sub(1)?),
+        };
+        map.get(next.0)?.get(next.1)?;
+        Some(next)
+    }
 }
 
-fn get_far_point_distance(map: &[Vec<MapTile>], start: (usize, usize)) -> usize {
-    let mut dist = 0;
-    let mut heap = BinaryHeap::new();
-    let mut visits: HashSet<(usize, usize)> = HashSet::new();
-    heap.push((Reverse(0), start));
+impl MapTile {
+    fn connects(&self, dir: Direction) -> bool {
+        match dir {
+            Direction::North => self.north,
+            Direction::South => self.south,
+            Direction::East => self.east,
+            Direction::West => self.west,
+        }
+    }
 
-    while let Some((Reverse(k), (i, j))) = heap.pop() {
-        if !visits.insert((i, j)) {
-            continue;
+    fn connect(&mut self, dir: Direction) {
+        match dir {
+            Direction::North => self.north = true,
+            Direction::South => self.south = true,
+            Direction::East => self.east = true,
+            Direction::West => self.west = true,
         }
-        dist = dist.max(k);
-        let c = map[i][j];
-        if c.north {
-            heap.push((Reverse(k + 1), (i - 1, j)));
+    }
+
+    fn with_connections(a: Direction, b: Direction) -> Self {
+        let mut tile = MapTile {
+            start: true,
+            ..Default::default()
+        };
+        tile.connect(a);
+        tile.connect(b);
+        tile
+    }
+}
+
+type Map = Vec<Vec<MapTile>>;
+
+/// Describes a position the way an editor would show it.
+fn describe((i, j): (usize, usize)) -> String {
+    format!("line {}, column {}", i + 1, j + 1)
+}
+
+/// Reads the map and finds the start tile, which is connected to every neighbour pointing at it.
+fn get_map(input: &str) -> Result<(Map, (usize, usize))> {
+    let mut map = input
+        .lines()
+        .map(|l| l.chars().map(MapTile::from).collect_vec())
+        .collect_vec();
+
+    let mut starts = map
+        .iter()
+        .enumerate()
+        .flat_map(|(i, row)| row.iter().positions(|tile| tile.start).map(move |j| (i, j)));
+    let start = starts.next().context("the map has no start tile `S`")?;
+    if let Some(other) = starts.next() {
+        bail!(
+            "the map has more than one start tile, at {} and {}",
+            describe(start),
+            describe(other)
+        );
+    }
+
+    let mut tile = map[start.0][start.1];
+    for dir in DIRECTIONS {
+        if let Some(next) = dir.step(start, &map) {
+            if map[next.0][next.1].connects(dir.opposite()) {
+                tile.connect(dir);
+            }
         }
-        if c.south {
-            heap.push((Reverse(k + 1), (i + 1, j)));
+    }
+    map[start.0][start.1] = tile;
+
+    Ok((map, start))
+}
+
+/// Follows the pipe leaving `start` towards `dir` until it gets back to `start`, checking that
+/// every pipe on the way connects back to the one before it.
+/// Returns the tiles of the loop in order, together with the direction the loop re-enters `start` from.
+fn walk_loop(
+    map: &[Vec<MapTile>],
+    start: (usize, usize),
+    mut dir: Direction,
+) -> Result<(Vec<(usize, usize)>, Direction)> {
+    let mut pos = start;
+    let mut path = vec![start];
+    loop {
+        let next = dir
+            .step(pos, map)
+            .with_context(|| format!("the pipe at {} leads off the map", describe(pos)))?;
+        let came_from = dir.opposite();
+        let tile = map[next.0][next.1];
+        if !tile.connects(came_from) {
+            bail!(
+                "the pipe at {} leads to {}, which does not connect back",
+                describe(pos),
+                describe(next)
+            );
         }
-        if c.west {
-            heap.push((Reverse(k + 1), (i, j - 1)));
+        if next == start {
+            return Ok((path, came_from));
         }
-        if c.east {
-            heap.push((Reverse(k + 1), (i, j + 1)));
+        path.push(next);
+        pos = next;
+        // every pipe has exactly two ends, so the walk can never revisit a tile other than `start`.
+        dir = DIRECTIONS
+            .into_iter()
+            .find(|d| *d != came_from && tile.connects(*d))
+            .with_context(|| format!("the pipe at {} is a dead end", describe(pos)))?;
+    }
+}
+
+/// Finds the loop through `start` and replaces the start tile with the pipe shape hidden under it.
+/// `get_map` connects `S` to every neighbour pointing at it, which can be more than the two loop pipes.
+fn extract_loop(map: &mut [Vec<MapTile>], start: (usize, usize)) -> Result<Vec<(usize, usize)>> {
+    let tile = map[start.0][start.1];
+    let mut last_err = None;
+    for first in DIRECTIONS.into_iter().filter(|d| tile.connects(*d)) {
+        match walk_loop(map, start, first) {
+            Ok((path, back)) => {
+                map[start.0][start.1] = MapTile::with_connections(first, back);
+                return Ok(path);
+            }
+            Err(err) => last_err = Some(err),
         }
     }
+    Err(match last_err {
+        Some(err) => err.context("no loop passes through the start tile"),
+        None => anyhow!("no pipe connects to the start tile at {}", describe(start)),
+    })
+}
 
-    dist
+/// Counts the tiles enclosed by the loop.
+/// The shoelace formula gives the area of the polygon through the tile centres, and Pick's theorem
+/// (`area = interior + boundary / 2 - 1`) turns that into the number of interior tiles.
+fn count_enclosed(path: &[(usize, usize)]) -> usize {
+    let twice_area: i64 = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(&(y1, x1), &(y2, x2))| (x1 as i64 * y2 as i64) - (x2 as i64 * y1 as i64))
+        .sum();
+    let boundary = path.len() as i64;
+    ((twice_area.abs() - boundary) / 2 + 1) as usize
 }
 
-// way too slow. todo!()
-pub fn part_one(input: &str) -> Option<usize> {
-    let (map, start) = get_map(input);
-    let res = get_far_point_distance(&map, start);
-    Some(res)
+/// The map with the first `steps` tiles of the loop highlighted.
+fn loop_grid(input: &str, path: &[(usize, usize)], steps: usize) -> Grid {
+    let mut grid = Grid::from_lines(input);
+    for &(i, j) in path.iter().take(steps) {
+        grid.highlight(j, i, Colour::Green);
+    }
+    grid.highlight(path[0].1, path[0].0, Colour::Yellow);
+    grid
 }
 
-pub fn part_two(_input: &str) -> Option<u32> {
-    None
+/// The farthest tile is halfway round the loop.
+pub fn part_one(input: &str) -> Result<usize> {
+    let (mut map, start) = get_map(input)?;
+    let path = extract_loop(&mut map, start)?;
+    let farthest = path.len() / 2;
+
+    if viz::enabled() {
+        let every = (path.len() / 30).max(1);
+        for steps in (every..path.len()).step_by(every) {
+            viz::frame("walking the loop", || loop_grid(input, &path, steps));
+        }
+        viz::frame("the farthest tile", || {
+            let mut grid = loop_grid(input, &path, path.len());
+            grid.highlight(path[farthest].1, path[farthest].0, Colour::Red);
+            grid
+        });
+    }
+
+    Ok(farthest)
+}
+
+pub fn part_two(input: &str) -> Result<usize> {
+    let (mut map, start) = get_map(input)?;
+    let path = extract_loop(&mut map, start)?;
+
+    // the count comes from the loop's area, this finds the tiles themselves to show them.
+    viz::frame("enclosed tiles", || {
+        let mut grid = loop_grid(input, &path, path.len());
+        let on_loop: hashbrown::HashSet<&(usize, usize)> = path.iter().collect();
+        for (i, row) in map.iter().enumerate() {
+            let mut inside = false;
+            for (j, tile) in row.iter().enumerate() {
+                if on_loop.contains(&(i, j)) {
+                    inside ^= tile.north;
+                } else if inside {
+                    grid.highlight(j, i, Colour::Magenta);
+                }
+            }
+        }
+        grid
+    });
+
+    Ok(count_enclosed(&path))
 }
 
 #[cfg(test)]
@@ -137,12 +299,98 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(8));
+        assert_eq!(result.unwrap(), 8);
     }
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
+        assert_eq!(result.unwrap(), 4);
+    }
+
+    #[test]
+    fn test_part_two_squeezing_between_pipes() {
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 3,
+        ));
+        assert_eq!(result.unwrap(), 4);
+    }
+
+    #[test]
+    fn test_part_two_larger_example() {
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 4,
+        ));
+        assert_eq!(result.unwrap(), 8);
+    }
+
+    #[test]
+    fn test_part_two_junk_pipes() {
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 5,
+        ));
+        assert_eq!(result.unwrap(), 10);
+    }
+
+    #[test]
+    fn test_infers_start_shape() {
+        let (mut map, start) = get_map(&advent_of_code::template::read_file_part(
+            "examples", DAY, 5,
+        ))
+        .unwrap();
+        extract_loop(&mut map, start).unwrap();
+        // the `S` in the last example is a `7`.
+        assert_eq!(
+            map[start.0][start.1],
+            MapTile {
+                start: true,
+                ..MapTile::from('7')
+            }
+        );
+    }
+
+    #[test]
+    fn test_part_one_returns_path_length() {
+        let (mut map, start) =
+            get_map(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let path = extract_loop(&mut map, start).unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!(path[0], start);
+    }
+
+    #[test]
+    fn test_rejects_missing_start() {
+        let err = part_one(".F7\n.LJ").unwrap_err();
+        assert_eq!(err.to_string(), "the map has no start tile `S`");
+    }
+
+    #[test]
+    fn test_rejects_pipes_leaving_the_map() {
+        let err = part_one("-S-").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "no loop passes through the start tile: the pipe at line 1, column 1 leads off the map"
+        );
+    }
+
+    #[test]
+    fn test_rejects_pipes_that_do_not_connect_back() {
+        let err = part_one("S-7\n|.|\nL-.").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "no loop passes through the start tile: \
+            the pipe at line 2, column 3 leads to line 3, column 3, which does not connect back"
+        );
+    }
+
+    #[test]
+    fn test_rejects_isolated_start() {
+        let err = part_two("...\n.S.\n...").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no pipe connects to the start tile at line 2, column 2"
+        );
     }
 }
@@ -1,6 +1,6 @@
-use advent_of_code::Point;
-use hashbrown::HashMap;
-use itertools::Itertools;
+use advent_of_code::parse::lines;
+use advent_of_code::viz::{self, Colour, Grid};
+use anyhow::{anyhow, ensure, Result};
 
 advent_of_code::solution!(11);
 
@@ -10,136 +10,279 @@ enum NodeType {
     Galaxy,
 }
 
-struct ParseNodeTypeError;
-
 impl TryFrom<char> for NodeType {
-    type Error = ParseNodeTypeError;
+    type Error = anyhow::Error;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
             '.' => Ok(NodeType::Space),
             '#' => Ok(NodeType::Galaxy),
-            _ => Err(ParseNodeTypeError),
+            c => Err(anyhow!("`{c}` is neither space (`.`) nor a galaxy (`#`)")),
         }
     }
 }
 
-fn parse_init_map(input: &str) -> (HashMap<Point, NodeType>, usize, usize) {
-    let mut map: HashMap<Point, NodeType> = HashMap::new();
-    let mut max_x = 0;
-    let mut max_y = 0;
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            if let Ok(t) = NodeType::try_from(c) {
-                map.insert_unique_unchecked(Point { x, y }, t);
-            }
-            max_x = max_x.max(x);
-        }
-        max_y = max_y.max(y);
-    }
-    (map, max_x, max_y)
+/// The galaxies of an image, and how many empty rows and columns come before each position.
+pub struct Universe {
+    /// `(x, y)` in the image as it was taken, numbered in reading order.
+    galaxies: Vec<(usize, usize)>,
+    empty_cols_before: Vec<u64>,
+    empty_rows_before: Vec<u64>,
 }
 
-struct ColsRows {
-    xs: Vec<usize>,
-    ys: Vec<usize>,
+/// `empty_before[i]` is the number of entries before `i` that are not `occupied`.
+fn count_empty_before(occupied: &[bool]) -> Vec<u64> {
+    occupied
+        .iter()
+        .scan(0, |empty, is_occupied| {
+            let before = *empty;
+            *empty += u64::from(!is_occupied);
+            Some(before)
+        })
+        .collect()
 }
 
-fn get_expanded_cols_rows(map: &HashMap<Point, NodeType>, x_len: usize, y_len: usize) -> ColsRows {
-    let mut xs: Vec<usize> = Vec::new();
-    for x in 0..x_len {
-        if (0..y_len).all(|y| {
-            map.get(&Point { x, y })
-                .is_some_and(|v| v == &NodeType::Space)
-        }) {
-            xs.push(x);
-        }
+/// Sums `|a - b|` over every pair of `coords`, which must be sorted.
+fn sum_pairwise_distances(coords: impl Iterator<Item = u128>) -> u128 {
+    let mut total = 0;
+    let mut prefix = 0;
+    for (i, c) in coords.enumerate() {
+        // `c` is at least every coordinate before it, so it is `i * c - prefix` away from them.
+        total += i as u128 * c - prefix;
+        prefix += c;
     }
+    total
+}
+
+impl Universe {
+    pub fn parse(input: &str) -> Result<Self> {
+        let rows = lines(input, |l| {
+            l.chars()
+                .enumerate()
+                .map(|(x, c)| {
+                    NodeType::try_from(c).map_err(|e| e.context(format!("column {}", x + 1)))
+                })
+                .collect::<Result<Vec<NodeType>>>()
+        })?;
 
-    let mut ys: Vec<usize> = Vec::new();
-    for y in 0..y_len {
-        if (0..x_len).all(|x| {
-            map.get(&Point { x, y })
-                .is_some_and(|v| v == &NodeType::Space)
-        }) {
-            ys.push(y);
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut galaxies = vec![];
+        let mut cols = vec![false; width];
+        let mut occupied_rows = vec![false; rows.len()];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, node) in row.iter().enumerate() {
+                if *node == NodeType::Galaxy {
+                    galaxies.push((x, y));
+                    cols[x] = true;
+                    occupied_rows[y] = true;
+                }
+            }
         }
+
+        Ok(Universe {
+            galaxies,
+            empty_cols_before: count_empty_before(&cols),
+            empty_rows_before: count_empty_before(&occupied_rows),
+        })
     }
-    ColsRows { xs, ys }
-}
 
-fn get_effective_point(p: &Point, expansions: &ColsRows, expand_times: usize) -> Point {
-    let effective_x = p.x + (expand_times * expansions.xs.iter().filter(|x| **x < p.x).count());
-    let effective_y = p.y + (expand_times * expansions.ys.iter().filter(|y| **y < p.y).count());
-    Point {
-        x: effective_x,
-        y: effective_y,
+    /// Where galaxy `ix` ends up once every empty row and column has become `factor` of them.
+    fn expanded(&self, ix: usize, factor: u64) -> (u128, u128) {
+        let (x, y) = self.galaxies[ix];
+        let growth = u128::from(factor.saturating_sub(1));
+        (
+            x as u128 + growth * u128::from(self.empty_cols_before[x]),
+            y as u128 + growth * u128::from(self.empty_rows_before[y]),
+        )
+    }
+
+    /// The sum of the distances between every pair of galaxies, once every empty row and column has
+    /// become `factor` of them.
+    ///
+    /// The distances along each axis add up separately, and sorting the coordinates on an axis
+    /// lets every galaxy be compared with all the ones before it at once.
+    pub fn total_distance(&self, factor: u64) -> u128 {
+        let expanded: Vec<(u128, u128)> = (0..self.galaxies.len())
+            .map(|ix| self.expanded(ix, factor))
+            .collect();
+        let mut xs: Vec<u128> = expanded.iter().map(|(x, _)| *x).collect();
+        xs.sort_unstable();
+        // galaxies are found in reading order, so their rows are sorted already.
+        let ys = expanded.iter().map(|(_, y)| *y);
+        sum_pairwise_distances(xs.into_iter()) + sum_pairwise_distances(ys)
     }
-}
 
-fn calc_distance(p1: &Point, p2: &Point, expansions: &ColsRows, expand_times: usize) -> usize {
-    let effective_p1 = get_effective_point(p1, expansions, expand_times);
-    let effective_p2 = get_effective_point(p2, expansions, expand_times);
-    let raw_distance_x = effective_p1.x.abs_diff(effective_p2.x);
-    let raw_distance_y = effective_p1.y.abs_diff(effective_p2.y);
-    raw_distance_x + raw_distance_y
+    /// The distance between two galaxies, numbered from 1 in reading order as in the puzzle.
+    pub fn distance(&self, a: usize, b: usize, factor: u64) -> Result<u128> {
+        let count = self.galaxies.len();
+        for n in [a, b] {
+            ensure!(
+                (1..=count).contains(&n),
+                "there is no galaxy {n}, the image has {count}"
+            );
+        }
+        let (ax, ay) = self.expanded(a - 1, factor);
+        let (bx, by) = self.expanded(b - 1, factor);
+        Ok(ax.abs_diff(bx) + ay.abs_diff(by))
+    }
 }
 
-fn get_distances(
-    map: &HashMap<Point, NodeType>,
-    expansions: &ColsRows,
-    expand_times: usize,
-) -> Vec<usize> {
-    let galaxies = map
-        .iter()
-        .filter_map(|(k, v)| match v {
-            NodeType::Galaxy => Some(k),
-            _ => None,
-        })
-        .tuple_combinations()
-        .map(|(p1, p2)| calc_distance(p1, p2, expansions, expand_times))
-        .collect_vec();
-    galaxies
+/// The image with its galaxies and the empty rows and columns, which are the ones that grow.
+fn expansion_grid(input: &str, universe: &Universe) -> Grid {
+    let mut grid = Grid::from_lines(input);
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            if universe.galaxies.iter().all(|g| g.0 != x || g.1 != y)
+                && (universe.galaxies.iter().all(|g| g.0 != x)
+                    || universe.galaxies.iter().all(|g| g.1 != y))
+            {
+                grid.highlight(x, y, Colour::Blue);
+            }
+        }
+    }
+    for &(x, y) in &universe.galaxies {
+        grid.highlight(x, y, Colour::Yellow);
+    }
+    grid
 }
 
-pub fn part_one(input: &str) -> Option<usize> {
-    let (map, max_x, max_y) = parse_init_map(input);
-    let expansions = get_expanded_cols_rows(&map, max_x + 1, max_y + 1);
-    let distances = get_distances(&map, &expansions, 1);
-    Some(distances.iter().sum())
-    //None
+pub fn part_one(input: &str) -> Result<u128> {
+    let universe = Universe::parse(input)?;
+    viz::frame("empty rows and columns", || {
+        expansion_grid(input, &universe)
+    });
+    Ok(universe.total_distance(2))
 }
 
-fn part_two_inner(input: &str, expand_times: usize) -> Option<usize> {
-    let (map, max_x, max_y) = parse_init_map(input);
-    let expansions = get_expanded_cols_rows(&map, max_x + 1, max_y + 1);
-    let distances = get_distances(&map, &expansions, expand_times);
-    Some(distances.iter().sum())
+/// Each empty row and column gains `expand_times` more.
+fn part_two_inner(input: &str, expand_times: u64) -> Result<u128> {
+    Ok(Universe::parse(input)?.total_distance(expand_times + 1))
 }
 
-pub fn part_two(input: &str) -> Option<usize> {
+pub fn part_two(input: &str) -> Result<u128> {
     part_two_inner(input, 1000000 - 1)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::property;
+
+    fn brute_force(universe: &Universe, factor: u64) -> u128 {
+        let count = universe.galaxies.len();
+        (1..=count)
+            .flat_map(|a| (a + 1..=count).map(move |b| (a, b)))
+            .map(|(a, b)| universe.distance(a, b, factor).unwrap())
+            .sum()
+    }
 
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(374));
+        assert_eq!(result.unwrap(), 374);
     }
 
     #[test]
     fn test_part_two_10() {
         let result = part_two_inner(&advent_of_code::template::read_file("examples", DAY), 9);
-        assert_eq!(result, Some(1030));
+        assert_eq!(result.unwrap(), 1030);
     }
 
     #[test]
     fn test_part_two_100() {
         let result = part_two_inner(&advent_of_code::template::read_file("examples", DAY), 99);
-        assert_eq!(result, Some(8410));
+        assert_eq!(result.unwrap(), 8410);
+    }
+
+    #[test]
+    fn test_distance_between_galaxies() {
+        let universe =
+            Universe::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        for (a, b, expected) in [(5, 9, 9), (1, 7, 15), (3, 6, 17), (8, 9, 5)] {
+            assert_eq!(universe.distance(a, b, 2).unwrap(), expected);
+        }
+        assert!(universe.distance(0, 1, 2).is_err());
+        assert!(universe.distance(1, 10, 2).is_err());
+    }
+
+    #[test]
+    fn test_matches_pairwise_sum() {
+        let universe =
+            Universe::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        for factor in [1, 2, 10, 1_000_000, u64::MAX] {
+            assert_eq!(
+                universe.total_distance(factor),
+                brute_force(&universe, factor)
+            );
+        }
+    }
+
+    /// Sums the distances after literally repeating every empty row and column `factor` times.
+    fn expand_and_measure(rows: &[Vec<bool>], factor: usize) -> u128 {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let is_galaxy = |x: usize, y: usize| rows[y].get(x).copied().unwrap_or(false);
+        let repeat = |empty: bool| if empty { factor } else { 1 };
+        let xs: Vec<usize> = (0..width)
+            .flat_map(|x| {
+                let empty = (0..rows.len()).all(|y| !is_galaxy(x, y));
+                std::iter::repeat_n(x, repeat(empty))
+            })
+            .collect();
+        let ys: Vec<usize> = (0..rows.len())
+            .flat_map(|y| std::iter::repeat_n(y, repeat(!rows[y].contains(&true))))
+            .collect();
+
+        let mut galaxies = vec![];
+        // empty rows and columns are the only ones repeated, so every galaxy is found once.
+        for (ey, y) in ys.iter().enumerate() {
+            for (ex, x) in xs.iter().enumerate() {
+                if is_galaxy(*x, *y) {
+                    galaxies.push((ex, ey));
+                }
+            }
+        }
+        let mut total = 0;
+        for (i, a) in galaxies.iter().enumerate() {
+            for b in &galaxies[i + 1..] {
+                total += (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u128;
+            }
+        }
+        total
+    }
+
+    #[test]
+    fn test_matches_literal_expansion() {
+        let image = |rows: &[Vec<bool>]| {
+            let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+            let line = |row: &Vec<bool>| {
+                (0..width)
+                    .map(|x| if row.get(x) == Some(&true) { '#' } else { '.' })
+                    .collect::<String>()
+            };
+            rows.iter().map(line).collect::<Vec<_>>().join("\n")
+        };
+        property::check_against(
+            "day 11 distances",
+            |rng| {
+                let width = rng.int(1..=8) as usize;
+                let rows = rng.vec(0..=8, |r| (0..width).map(|_| r.below(5) == 0).collect());
+                (rows, rng.below(4))
+            },
+            |(rows, extra)| expand_and_measure(rows, extra + 1),
+            |(rows, extra)| {
+                Universe::parse(&image(rows))
+                    .unwrap()
+                    .total_distance(*extra as u64 + 1)
+            },
+        );
+    }
+
+    #[test]
+    fn test_reports_unknown_characters() {
+        let err = part_one("#..\n.x#").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 2: column 2: `x` is neither space (`.`) nor a galaxy (`#`)"
+        );
     }
 }
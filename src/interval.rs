@@ -0,0 +1,350 @@
+/// Sets of integers stored as ranges, and piecewise maps that shift ranges of integers.
+///
+/// Ranges are half-open (`start..end`) throughout. An [`IntervalSet`] keeps its ranges sorted,
+/// disjoint and merged, so two sets containing the same integers always compare equal.
+use std::ops::Range;
+
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a set from ranges that may overlap, touch or be empty.
+    ///
+    /// ```
+    /// # use advent_of_code::interval::IntervalSet;
+    /// let set = IntervalSet::from_ranges([5..8, 0..2, 1..3, 8..9, 4..4]);
+    /// assert_eq!(set.ranges(), &[0..3, 5..9]);
+    /// ```
+    pub fn from_ranges(ranges: impl IntoIterator<Item = Range<i64>>) -> Self {
+        let mut ranges: Vec<Range<i64>> = ranges.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_by_key(|r| r.start);
+
+        let mut merged: Vec<Range<i64>> = Vec::with_capacity(ranges.len());
+        for r in ranges {
+            match merged.last_mut() {
+                Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+        IntervalSet { ranges: merged }
+    }
+
+    pub fn ranges(&self) -> &[Range<i64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of integers in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|r| r.end.abs_diff(r.start)).sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.ranges.last().map(|r| r.end - 1)
+    }
+
+    pub fn contains(&self, point: i64) -> bool {
+        let ix = self.ranges.partition_point(|r| r.end <= point);
+        self.ranges.get(ix).is_some_and(|r| r.contains(&point))
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_ranges(self.ranges.iter().chain(&other.ranges).cloned())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut res = vec![];
+        let (mut a, mut b) = (0, 0);
+        while let (Some(x), Some(y)) = (self.ranges.get(a), other.ranges.get(b)) {
+            let overlap = x.start.max(y.start)..x.end.min(y.end);
+            if !overlap.is_empty() {
+                res.push(overlap);
+            }
+            if x.end < y.end {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+        IntervalSet { ranges: res }
+    }
+
+    /// The integers in `self` that are not in `other`.
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut res = vec![];
+        let mut others = other.ranges.iter().peekable();
+        for r in &self.ranges {
+            let mut start = r.start;
+            while let Some(o) = others.next_if(|o| o.end <= r.end) {
+                if o.start > start {
+                    res.push(start..o.start);
+                }
+                start = start.max(o.end);
+            }
+            // a range of `other` that continues past this one can still cut off its end.
+            let end = others.peek().map_or(r.end, |o| o.start.clamp(start, r.end));
+            if start < end {
+                res.push(start..end);
+            }
+        }
+        IntervalSet { ranges: res }
+    }
+
+    /// Moves every integer in the set by `offset`.
+    pub fn shift(&self, offset: i64) -> IntervalSet {
+        IntervalSet {
+            ranges: self
+                .ranges
+                .iter()
+                .map(|r| r.start + offset..r.end + offset)
+                .collect(),
+        }
+    }
+
+    /// Cuts the set at the boundaries of `map`'s pieces, pairing each part with the offset `map`
+    /// moves it by.
+    pub fn split(&self, map: &OffsetMap) -> Vec<(Range<i64>, i64)> {
+        self.ranges
+            .iter()
+            .flat_map(|r| map.segments(r.clone()))
+            .collect()
+    }
+}
+
+/// A function on integers that adds a different offset to each of a number of disjoint ranges,
+/// and leaves integers outside those ranges unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetMap {
+    /// Sorted, disjoint and never with an offset of 0.
+    pieces: Vec<(Range<i64>, i64)>,
+}
+
+impl OffsetMap {
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    /// Builds a map from `(range, offset)` pieces, failing if two pieces overlap.
+    pub fn from_pieces(pieces: impl IntoIterator<Item = (Range<i64>, i64)>) -> Result<Self> {
+        let mut pieces: Vec<(Range<i64>, i64)> =
+            pieces.into_iter().filter(|(r, _)| !r.is_empty()).collect();
+        pieces.sort_by_key(|(r, _)| r.start);
+        if let Some(w) = pieces.windows(2).find(|w| w[1].0.start < w[0].0.end) {
+            bail!("the ranges {:?} and {:?} overlap", w[0].0, w[1].0);
+        }
+        Ok(Self::normalised(pieces))
+    }
+
+    /// Merges touching pieces with the same offset and drops pieces that do not move anything.
+    fn normalised(pieces: Vec<(Range<i64>, i64)>) -> Self {
+        let mut res: Vec<(Range<i64>, i64)> = Vec::with_capacity(pieces.len());
+        for (r, offset) in pieces.into_iter().filter(|(_, o)| *o != 0) {
+            match res.last_mut() {
+                Some((last, o)) if last.end == r.start && *o == offset => last.end = r.end,
+                _ => res.push((r, offset)),
+            }
+        }
+        OffsetMap { pieces: res }
+    }
+
+    pub fn pieces(&self) -> &[(Range<i64>, i64)] {
+        &self.pieces
+    }
+
+    pub fn get(&self, point: i64) -> i64 {
+        let ix = self.pieces.partition_point(|(r, _)| r.end <= point);
+        match self.pieces.get(ix) {
+            Some((r, offset)) if r.contains(&point) => point + offset,
+            _ => point,
+        }
+    }
+
+    /// Cuts `range` at the boundaries of the pieces, including the unmapped gaps between them.
+    fn segments(&self, range: Range<i64>) -> Vec<(Range<i64>, i64)> {
+        let mut res = vec![];
+        let mut start = range.start;
+        let first = self.pieces.partition_point(|(r, _)| r.end <= start);
+        for (r, offset) in &self.pieces[first..] {
+            if start >= range.end {
+                break;
+            }
+            if r.start > start {
+                let end = r.start.min(range.end);
+                res.push((start..end, 0));
+                start = end;
+            }
+            let end = r.end.min(range.end);
+            if start < end {
+                res.push((start..end, *offset));
+                start = end;
+            }
+        }
+        if start < range.end {
+            res.push((start..range.end, 0));
+        }
+        res
+    }
+
+    /// The image of `set` under the map.
+    pub fn apply(&self, set: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_ranges(
+            set.split(self)
+                .into_iter()
+                .map(|(r, offset)| r.start + offset..r.end + offset),
+        )
+    }
+
+    /// The integers the map sends into `set`.
+    /// Several integers can land on the same one, so this is a set rather than a single inverse.
+    pub fn preimage(&self, set: &IntervalSet) -> IntervalSet {
+        let moved = IntervalSet::from_ranges(self.pieces.iter().map(|(r, _)| r.clone()));
+        let mut res = set.difference(&moved);
+        for (r, offset) in &self.pieces {
+            let landing = set
+                .shift(-offset)
+                .intersection(&IntervalSet::from_ranges([r.clone()]));
+            res = res.union(&landing);
+        }
+        res
+    }
+
+    /// The map that applies `self` and then `next`, as a single piecewise map.
+    ///
+    /// ```
+    /// # use advent_of_code::interval::OffsetMap;
+    /// let a = OffsetMap::from_pieces([(0..10, 5)]).unwrap();
+    /// let b = OffsetMap::from_pieces([(8..12, -8)]).unwrap();
+    /// let both = a.then(&b);
+    /// assert_eq!([0, 3, 5, 7, 12].map(|x| both.get(x)), [5, 0, 2, 12, 12]);
+    /// ```
+    pub fn then(&self, next: &OffsetMap) -> OffsetMap {
+        let mut pieces = vec![];
+
+        // integers `self` leaves alone are only moved by `next`.
+        let moved = IntervalSet::from_ranges(self.pieces.iter().map(|(r, _)| r.clone()));
+        for (r, offset) in &next.pieces {
+            let unmoved = IntervalSet::from_ranges([r.clone()]).difference(&moved);
+            pieces.extend(unmoved.ranges.into_iter().map(|r| (r, *offset)));
+        }
+
+        // the rest are moved by `self`, then by whichever piece of `next` they land in.
+        for (r, offset) in &self.pieces {
+            let image = r.start + offset..r.end + offset;
+            pieces.extend(
+                next.segments(image)
+                    .into_iter()
+                    .map(|(s, o)| (s.start - offset..s.end - offset, offset + o)),
+            );
+        }
+
+        pieces.sort_by_key(|(r, _)| r.start);
+        Self::normalised(pieces)
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{IntervalSet, OffsetMap};
+
+    fn set(ranges: &[std::ops::Range<i64>]) -> IntervalSet {
+        IntervalSet::from_ranges(ranges.iter().cloned())
+    }
+
+    fn members(set: &IntervalSet, within: std::ops::Range<i64>) -> Vec<i64> {
+        within.filter(|x| set.contains(*x)).collect()
+    }
+
+    #[test]
+    fn set_operations_match_pointwise() {
+        let a = set(&[0..5, 8..12, 15..16, 20..30]);
+        let b = set(&[3..9, 11..21, 25..26, 29..40]);
+        let within = -5..45;
+        let pointwise = |f: fn(bool, bool) -> bool| -> Vec<i64> {
+            within
+                .clone()
+                .filter(|x| f(a.contains(*x), b.contains(*x)))
+                .collect()
+        };
+
+        assert_eq!(
+            members(&a.union(&b), within.clone()),
+            pointwise(|x, y| x || y)
+        );
+        assert_eq!(
+            members(&a.intersection(&b), within.clone()),
+            pointwise(|x, y| x && y)
+        );
+        assert_eq!(
+            members(&a.difference(&b), within.clone()),
+            pointwise(|x, y| x && !y)
+        );
+        assert_eq!(
+            members(&b.difference(&a), within.clone()),
+            pointwise(|x, y| y && !x)
+        );
+    }
+
+    #[test]
+    fn counts_and_bounds() {
+        let s = set(&[10..20, -5..0]);
+        assert_eq!(s.len(), 15);
+        assert_eq!((s.min(), s.max()), (Some(-5), Some(19)));
+        assert!(IntervalSet::new().is_empty());
+    }
+
+    #[test]
+    fn splits_by_map_pieces() {
+        let map = OffsetMap::from_pieces([(98..100, -48), (50..98, 2)]).unwrap();
+        assert_eq!(
+            set(&[40..60, 95..105]).split(&map),
+            vec![
+                (40..50, 0),
+                (50..60, 2),
+                (95..98, 2),
+                (98..100, -48),
+                (100..105, 0)
+            ]
+        );
+        assert_eq!(map.apply(&set(&[79..93])), set(&[81..95]));
+    }
+
+    #[test]
+    fn rejects_overlapping_pieces() {
+        assert!(OffsetMap::from_pieces([(0..10, 1), (9..12, 2)]).is_err());
+    }
+
+    #[test]
+    fn preimage_matches_pointwise() {
+        let map = OffsetMap::from_pieces([(0..10, 5), (20..25, -20), (30..35, 100)]).unwrap();
+        let target = set(&[3..8, 12..16, 131..140]);
+        let expected: Vec<i64> = (-5..50).filter(|x| target.contains(map.get(*x))).collect();
+        assert_eq!(members(&map.preimage(&target), -5..50), expected);
+    }
+
+    #[test]
+    fn composition_matches_applying_in_turn() {
+        let a = OffsetMap::from_pieces([(0..10, 5), (20..25, -20), (30..35, 100)]).unwrap();
+        let b = OffsetMap::from_pieces([(3..12, 10), (14..22, -14), (130..132, -130)]).unwrap();
+        let both = a.then(&b);
+        for x in -5..150 {
+            assert_eq!(both.get(x), b.get(a.get(x)), "x = {x}");
+        }
+        assert_eq!(a.then(&OffsetMap::identity()), a);
+        assert_eq!(OffsetMap::identity().then(&b), b);
+    }
+}
@@ -1,5 +1,11 @@
 mod day;
+pub mod interval;
+pub mod ocr;
+pub mod parse;
+pub mod property;
 pub mod template;
+pub mod trace;
+pub mod viz;
 
 pub use day::*;
 
@@ -11,13 +17,18 @@ pub struct Point {
     pub y: usize,
 }
 
-pub fn lcm(nums: &[usize]) -> usize {
+/// The least common multiple of `nums`, or `None` if it does not fit in a `usize`.
+#[must_use]
+pub fn lcm(nums: &[usize]) -> Option<usize> {
     if nums.len() == 1 {
-        return nums[0];
+        return Some(nums[0]);
     }
     let a = nums[0];
-    let b = lcm(&nums[1..]);
-    a * b / gcd(a, b)
+    let b = lcm(&nums[1..])?;
+    match gcd(a, b) {
+        0 => Some(0),
+        d => (a / d).checked_mul(b),
+    }
 }
 
 pub fn gcd(a: usize, b: usize) -> usize {
@@ -26,3 +37,20 @@ pub fn gcd(a: usize, b: usize) -> usize {
     }
     gcd(b, a % b)
 }
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::lcm;
+
+    #[test]
+    fn finds_least_common_multiples() {
+        assert_eq!(lcm(&[4, 6, 10]), Some(60));
+        assert_eq!(lcm(&[usize::MAX, usize::MAX]), Some(usize::MAX));
+        assert_eq!(lcm(&[0, 0]), Some(0));
+    }
+
+    #[test]
+    fn reports_overflowing_multiples() {
+        assert_eq!(lcm(&[usize::MAX, 2]), None);
+    }
+}
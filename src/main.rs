@@ -1,12 +1,19 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve};
+use advent_of_code::template::commands::{
+    all, check_input, download, read, scaffold, solve, stars,
+};
 use args::{parse, AppArguments};
 
 mod args {
     use std::process;
 
+    use advent_of_code::template::benchmark_report::Report;
+    use advent_of_code::template::commands::solve::RunnerFlags;
     use advent_of_code::Day;
 
     pub enum AppArguments {
+        CheckInput {
+            day: Day,
+        },
         Download {
             day: Day,
         },
@@ -19,13 +26,19 @@ mod args {
         Solve {
             day: Day,
             release: bool,
-            time: bool,
             submit: Option<u8>,
             dhat: bool,
+            trace: bool,
+            flags: RunnerFlags,
         },
         All {
             release: bool,
             time: bool,
+            timeout: Option<f64>,
+            report: Option<Report>,
+        },
+        Stars {
+            timeout: Option<f64>,
         },
     }
 
@@ -36,10 +49,18 @@ mod args {
             Some("all") => AppArguments::All {
                 release: args.contains("--release"),
                 time: args.contains("--time"),
+                timeout: args.opt_value_from_str("--timeout")?,
+                report: Report::from_args(
+                    args.opt_value_from_str("--report")?,
+                    args.opt_value_from_str("--out")?,
+                )?,
             },
             Some("download") => AppArguments::Download {
                 day: args.free_from_str()?,
             },
+            Some("check-input") => AppArguments::CheckInput {
+                day: args.free_from_str()?,
+            },
             Some("read") => AppArguments::Read {
                 day: args.free_from_str()?,
             },
@@ -50,8 +71,26 @@ mod args {
                 day: args.free_from_str()?,
                 release: args.contains("--release"),
                 submit: args.opt_value_from_str("--submit")?,
-                time: args.contains("--time"),
                 dhat: args.contains("--dhat"),
+                trace: args.contains("--trace"),
+                flags: RunnerFlags {
+                    time: args.contains("--time"),
+                    json: args.contains("--json"),
+                    timeout: args.opt_value_from_str("--timeout")?,
+                    explain: args.contains("--explain"),
+                    variant: args.opt_value_from_str("--variant")?,
+                    compare: args.contains("--compare"),
+                    fuzz: args.contains("--fuzz"),
+                    runs: args.opt_value_from_str("--runs")?,
+                    seed: args.opt_value_from_str("--seed")?,
+                    viz: args.contains("--viz"),
+                    viz_format: args.opt_value_from_str("--viz-format")?,
+                    gif: args.contains("--gif"),
+                    profile_cpu: args.contains("--profile-cpu"),
+                },
+            },
+            Some("stars") => AppArguments::Stars {
+                timeout: args.opt_value_from_str("--timeout")?,
             },
             Some(x) => {
                 eprintln!("Unknown command: {x}");
@@ -79,17 +118,25 @@ fn main() {
             std::process::exit(1);
         }
         Ok(args) => match args {
-            AppArguments::All { release, time } => all::handle(release, time),
+            AppArguments::All {
+                release,
+                time,
+                timeout,
+                report,
+            } => all::handle(release, time, timeout, report),
+            AppArguments::CheckInput { day } => check_input::handle(day),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold { day } => scaffold::handle(day),
             AppArguments::Solve {
                 day,
                 release,
-                time,
                 submit,
                 dhat,
-            } => solve::handle(day, release, time, submit, dhat),
+                trace,
+                flags,
+            } => solve::handle(day, release, submit, dhat, trace, &flags),
+            AppArguments::Stars { timeout } => stars::handle(timeout),
         },
     };
 }
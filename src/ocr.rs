@@ -0,0 +1,254 @@
+/// Decodes the block-letter answers some puzzles draw on a grid.
+///
+/// Both fonts Advent of Code uses are supported: the small font that is 6 rows high (letters are
+/// usually 4 columns wide) and the large font that is 10 rows high (letters are 6 columns wide).
+/// Lit cells are `#` or `█`, every other character counts as unlit.
+use anyhow::{bail, Result};
+
+const SMALL_FONT: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const LARGE_FONT: [(char, &str); 15] = [
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+type Cells = Vec<Vec<bool>>;
+
+fn is_lit(c: char) -> bool {
+    c == '#' || c == '█'
+}
+
+fn to_cells(grid: &str) -> Cells {
+    let rows: Cells = grid
+        .lines()
+        .map(|l| l.chars().map(is_lit).collect())
+        .collect();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    rows.into_iter()
+        .map(|mut r| {
+            r.resize(width, false);
+            r
+        })
+        .collect()
+}
+
+/// Removes unlit rows above and below the drawing.
+fn trim_rows(cells: Cells) -> Cells {
+    let first = cells.iter().position(|r| r.contains(&true));
+    let last = cells.iter().rposition(|r| r.contains(&true));
+    match (first, last) {
+        (Some(first), Some(last)) => cells[first..=last].to_vec(),
+        _ => vec![],
+    }
+}
+
+/// Splits a drawing into letters at every fully unlit column.
+fn split_letters(cells: &Cells) -> Vec<Cells> {
+    let width = cells.first().map_or(0, Vec::len);
+    let mut letters = vec![];
+    let mut start: Option<usize> = None;
+
+    for x in 0..=width {
+        let is_blank = x == width || cells.iter().all(|r| !r[x]);
+        match (start, is_blank) {
+            (None, false) => start = Some(x),
+            (Some(s), true) => {
+                letters.push(cells.iter().map(|r| r[s..x].to_vec()).collect());
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    letters
+}
+
+fn glyph_cells(glyph: &str) -> Cells {
+    let cells = to_cells(glyph);
+    // glyphs carry their font's padding, letters cut from a drawing do not.
+    split_letters(&cells).pop().unwrap_or_default()
+}
+
+fn match_letter(letter: &Cells, font: &[(char, &str)]) -> Option<char> {
+    font.iter()
+        .find(|(_, glyph)| &glyph_cells(glyph) == letter)
+        .map(|(c, _)| *c)
+}
+
+/// Reads the letters drawn in `grid`.
+///
+/// Fails if the drawing does not have the height of a known font or contains a shape that is not a
+/// known letter.
+///
+/// ```
+/// # use advent_of_code::ocr::decode;
+/// let grid = "#..#.###.\n#..#..#..\n####..#..\n#..#..#..\n#..#..#..\n#..#.###.";
+/// assert_eq!(decode(grid).unwrap(), "HI");
+/// ```
+pub fn decode(grid: &str) -> Result<String> {
+    let cells = trim_rows(to_cells(grid));
+    let font: &[(char, &str)] = match cells.len() {
+        6 => &SMALL_FONT,
+        10 => &LARGE_FONT,
+        0 => bail!("the grid has no lit cells"),
+        h => bail!("the grid is {h} rows high, expecting 6 or 10"),
+    };
+
+    split_letters(&cells)
+        .iter()
+        .enumerate()
+        .map(|(ix, letter)| {
+            match_letter(letter, font)
+                .ok_or_else(|| anyhow::anyhow!("letter {} is not a known glyph", ix + 1))
+        })
+        .collect()
+}
+
+/// Draws a grid of cells as lines of `#` (lit) and `.` (unlit), ready to be returned from a part.
+pub fn render(cells: &[Vec<bool>]) -> String {
+    cells
+        .iter()
+        .map(|r| {
+            r.iter()
+                .map(|c| if *c { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{decode, render, to_cells, LARGE_FONT, SMALL_FONT};
+
+    fn join_glyphs(glyphs: &[&str], gap: usize) -> String {
+        let rows: Vec<Vec<&str>> = glyphs.iter().map(|g| g.lines().collect()).collect();
+        (0..rows[0].len())
+            .map(|y| {
+                rows.iter()
+                    .map(|r| r[y])
+                    .collect::<Vec<_>>()
+                    .join(&".".repeat(gap))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn decodes_every_small_glyph() {
+        for (c, glyph) in SMALL_FONT {
+            assert_eq!(decode(glyph).unwrap(), c.to_string());
+        }
+    }
+
+    #[test]
+    fn decodes_every_large_glyph() {
+        for (c, glyph) in LARGE_FONT {
+            assert_eq!(decode(glyph).unwrap(), c.to_string());
+        }
+    }
+
+    #[test]
+    fn decodes_small_words() {
+        let grid = join_glyphs(&[SMALL_FONT[7].1, SMALL_FONT[14].1, SMALL_FONT[3].1], 1);
+        assert_eq!(decode(&grid).unwrap(), "ISE");
+    }
+
+    #[test]
+    fn decodes_large_words_with_padding() {
+        let grid = join_glyphs(&[LARGE_FONT[6].1, LARGE_FONT[0].1], 2);
+        let padded = format!("\n{}\n\n", grid.replace('.', " "));
+        assert_eq!(decode(&padded).unwrap(), "HA");
+    }
+
+    #[test]
+    fn rejects_unknown_shapes() {
+        assert!(decode("#\n#\n#").is_err());
+        assert!(decode("....\n....").is_err());
+        let err = decode("####\n####\n####\n####\n####\n####").unwrap_err();
+        assert_eq!(err.to_string(), "letter 1 is not a known glyph");
+    }
+
+    #[test]
+    fn renders_cells() {
+        let grid = SMALL_FONT[0].1;
+        assert_eq!(render(&to_cells(grid)), grid);
+    }
+}
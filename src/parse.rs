@@ -0,0 +1,237 @@
+/// Small parsing helpers shared between solutions.
+///
+/// Helpers report failures as [`anyhow::Error`]s that carry the column (and, via [`lines`], the
+/// line) the problem was found at, so that malformed input never has to panic.
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+/// An integer found in a line, together with the span it was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Located<T> {
+    pub value: T,
+    /// Zero-based byte offset of the first character of the number.
+    pub column: usize,
+    /// Length of the number in bytes, including a leading `-` if any.
+    pub len: usize,
+}
+
+fn parse_token<T: FromStr>(token: &str, column: usize) -> Result<T> {
+    token
+        .parse::<T>()
+        .map_err(|_| anyhow!("column {}: `{token}` is not a valid integer", column + 1))
+}
+
+fn scan_ints<T: FromStr>(line: &str, signed: bool) -> Result<Vec<Located<T>>> {
+    let bytes = line.as_bytes();
+    let mut res = vec![];
+    let mut ix = 0;
+
+    while ix < bytes.len() {
+        let is_negative = signed
+            && bytes[ix] == b'-'
+            && bytes.get(ix + 1).is_some_and(u8::is_ascii_digit)
+            && (ix == 0 || !bytes[ix - 1].is_ascii_alphanumeric());
+
+        if !is_negative && !bytes[ix].is_ascii_digit() {
+            ix += 1;
+            continue;
+        }
+
+        let start = ix;
+        ix += 1;
+        while ix < bytes.len() && bytes[ix].is_ascii_digit() {
+            ix += 1;
+        }
+
+        res.push(Located {
+            value: parse_token(&line[start..ix], start)?,
+            column: start,
+            len: ix - start,
+        });
+    }
+
+    Ok(res)
+}
+
+/// Extracts every run of ASCII digits in `line` as an unsigned integer, ignoring anything else.
+///
+/// ```
+/// # use advent_of_code::parse::unsigned_ints;
+/// assert_eq!(unsigned_ints::<u32>("Card 1: 41 48 | 83").unwrap(), vec![1, 41, 48, 83]);
+/// ```
+pub fn unsigned_ints<T: FromStr>(line: &str) -> Result<Vec<T>> {
+    Ok(unsigned_ints_located(line)?
+        .into_iter()
+        .map(|n| n.value)
+        .collect())
+}
+
+/// Like [`unsigned_ints`], but keeps the position of each number.
+pub fn unsigned_ints_located<T: FromStr>(line: &str) -> Result<Vec<Located<T>>> {
+    scan_ints(line, false)
+}
+
+/// Extracts every integer in `line`, treating a `-` directly in front of digits as a sign.
+/// A dash between two words (e.g. `seed-to-soil`) is not treated as a sign.
+pub fn signed_ints<T: FromStr>(line: &str) -> Result<Vec<T>> {
+    Ok(signed_ints_located(line)?
+        .into_iter()
+        .map(|n| n.value)
+        .collect())
+}
+
+/// Like [`signed_ints`], but keeps the position of each number.
+pub fn signed_ints_located<T: FromStr>(line: &str) -> Result<Vec<Located<T>>> {
+    scan_ints(line, true)
+}
+
+fn exact<T, const N: usize>(nums: Vec<T>) -> Result<[T; N]> {
+    let count = nums.len();
+    nums.try_into()
+        .map_err(|_| anyhow!("expected {N} integers, found {count}"))
+}
+
+/// Like [`unsigned_ints`], but fails unless exactly `N` integers are found.
+pub fn unsigned_ints_exact<T: FromStr, const N: usize>(line: &str) -> Result<[T; N]> {
+    exact(unsigned_ints(line)?)
+}
+
+/// Like [`signed_ints`], but fails unless exactly `N` integers are found.
+pub fn signed_ints_exact<T: FromStr, const N: usize>(line: &str) -> Result<[T; N]> {
+    exact(signed_ints(line)?)
+}
+
+/// Splits `input` into blocks separated by one or more blank lines.
+/// Leading and trailing blank lines are ignored, and `\r\n` line endings are accepted.
+pub fn blocks(input: &str) -> Vec<&str> {
+    let mut res = vec![];
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+    let mut offset = 0;
+
+    for line in input.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        if content.trim().is_empty() {
+            if let Some(s) = start.take() {
+                res.push(&input[s..end]);
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + content.len();
+        }
+        offset += line.len();
+    }
+
+    if let Some(s) = start {
+        res.push(&input[s..end]);
+    }
+
+    res
+}
+
+/// Splits a `key: values` record into its trimmed key and values.
+///
+/// ```
+/// # use advent_of_code::parse::record;
+/// assert_eq!(record("Game 1: 3 blue").unwrap(), ("Game 1", "3 blue"));
+/// ```
+pub fn record(line: &str) -> Result<(&str, &str)> {
+    let Some((key, values)) = line.split_once(':') else {
+        bail!("column 1: expected a `key: values` record, found `{line}`");
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        bail!("column 1: record has an empty key");
+    }
+    Ok((key, values.trim()))
+}
+
+/// Parses every non-empty line of `input` with `parse`, adding the line number to any error.
+pub fn lines<T>(input: &str, mut parse: impl FnMut(&str) -> Result<T>) -> Result<Vec<T>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(ix, l)| parse(l).with_context(|| format!("line {}", ix + 1)))
+        .collect()
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_unsigned_ints() {
+        let res: Vec<u32> = unsigned_ints("Game 12: 3 blue, 4 red; -1 green").unwrap();
+        assert_eq!(res, vec![12, 3, 4, 1]);
+    }
+
+    #[test]
+    fn extracts_signed_ints() {
+        let res: Vec<i64> = signed_ints("0 -3 6 -9 12").unwrap();
+        assert_eq!(res, vec![0, -3, 6, -9, 12]);
+    }
+
+    #[test]
+    fn dash_between_words_is_not_a_sign() {
+        let res: Vec<i64> = signed_ints("seed-to-soil map: 50-98").unwrap();
+        assert_eq!(res, vec![50, 98]);
+    }
+
+    #[test]
+    fn located_ints_keep_columns() {
+        let res: Vec<Located<u32>> = unsigned_ints_located("467..114..").unwrap();
+        assert_eq!(
+            res,
+            vec![
+                Located {
+                    value: 467,
+                    column: 0,
+                    len: 3
+                },
+                Located {
+                    value: 114,
+                    column: 5,
+                    len: 3
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn overflow_reports_column() {
+        let err = unsigned_ints::<u8>("1 2 300").unwrap_err();
+        assert_eq!(err.to_string(), "column 5: `300` is not a valid integer");
+    }
+
+    #[test]
+    fn exact_ints() {
+        let [a, b, c] = signed_ints_exact::<i64, 3>("50 98 2").unwrap();
+        assert_eq!((a, b, c), (50, 98, 2));
+        let err = signed_ints_exact::<i64, 3>("50 98").unwrap_err();
+        assert_eq!(err.to_string(), "expected 3 integers, found 2");
+    }
+
+    #[test]
+    fn splits_blocks() {
+        let res = blocks("\na\nb\n\n\nc\r\n\r\nd\n\n");
+        assert_eq!(res, vec!["a\nb", "c", "d"]);
+    }
+
+    #[test]
+    fn splits_records() {
+        assert_eq!(
+            record("Card  1: 41 48 | 83").unwrap(),
+            ("Card  1", "41 48 | 83")
+        );
+        assert!(record("no separator").is_err());
+        assert!(record(": 1 2").is_err());
+    }
+
+    #[test]
+    fn lines_adds_line_context() {
+        let err = lines("1\n2\nx", |l| signed_ints_exact::<i64, 1>(l)).unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 3: expected 1 integers, found 0");
+    }
+}
@@ -0,0 +1,469 @@
+//! Randomised property and differential tests for solutions.
+//!
+//! A test names a property, a generator for its inputs and a check that every input has to pass,
+//! usually that a solution agrees with a slow but obviously correct reference implementation:
+//!
+//! ```ignore
+//! property::check_against(
+//!     "count_wins",
+//!     |rng| (rng.int(0..=200), rng.int(0..=10_000)),
+//!     |(time, distance)| brute_force(*time, *distance),
+//!     |(time, distance)| count_wins(*time, *distance),
+//! );
+//! ```
+//!
+//! When an input fails, it is shrunk to the smallest input that still fails before the test
+//! panics. Runs are reproducible: the seed is derived from the property's name, and can be
+//! overridden with `AOC_PROPERTY_SEED`. `AOC_PROPERTY_CASES` sets how many inputs are tried.
+use std::fmt::{Debug, Display};
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+use std::{env, fmt};
+
+/// The 64-bit FNV-1a hash of `bytes`. Unlike the std hasher it is fixed, so seeds and file names
+/// derived from it stay the same across Rust releases.
+#[must_use]
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+/// A small, fast generator (SplitMix64). Not suitable for anything but tests.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn int(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (lo, hi) = (i128::from(*range.start()), i128::from(*range.end()));
+        assert!(lo <= hi, "cannot pick a number from an empty range");
+        let span = (hi - lo + 1) as u128;
+        (lo + (u128::from(self.next_u64()) % span) as i128) as i64
+    }
+
+    /// An index below `n`, which must be positive.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.int(0..=(n as i64 - 1)) as usize
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// A vector with a length in `len`, filled by `item`.
+    pub fn vec<T>(
+        &mut self,
+        len: RangeInclusive<usize>,
+        mut item: impl FnMut(&mut Self) -> T,
+    ) -> Vec<T> {
+        let len = self.int(*len.start() as i64..=*len.end() as i64) as usize;
+        (0..len).map(|_| item(self)).collect()
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Values that can be made smaller, so a failing input can be reduced to a minimal one.
+pub trait Shrink: Sized {
+    /// Candidates that are simpler than `self`, the most promising ones first.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! impl_shrink_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                fn shrink(&self) -> Vec<Self> {
+                    let x = *self;
+                    let mut res = vec![0, x / 2, x - x.signum()];
+                    if x < 0 {
+                        // a positive number is simpler than a negative one of the same size.
+                        res.insert(1, x.saturating_neg());
+                    }
+                    res.dedup();
+                    res.retain(|c| *c != x);
+                    res
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_shrink_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                fn shrink(&self) -> Vec<Self> {
+                    let x = *self;
+                    let mut res = vec![0, x / 2, x.saturating_sub(1)];
+                    res.dedup();
+                    res.retain(|c| *c != x);
+                    res
+                }
+            }
+        )*
+    };
+}
+
+impl_shrink_signed!(i8, i16, i32, i64, i128, isize);
+impl_shrink_unsigned!(u8, u16, u32, u64, u128, usize);
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self {
+            vec![false]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl Shrink for char {
+    fn shrink(&self) -> Vec<Self> {
+        ['a', '.', '0'].into_iter().filter(|c| c < self).collect()
+    }
+}
+
+impl<T: Shrink + Clone + PartialEq> Shrink for Vec<T> {
+    /// Shorter vectors first: empty, either half, every element removed, then every element
+    /// shrunk. Candidates that one of the earlier ones already covers are left out.
+    fn shrink(&self) -> Vec<Self> {
+        let mut res = vec![];
+        if self.is_empty() {
+            return res;
+        }
+        res.push(vec![]);
+        // a half that is only one element short is also one of the removals below.
+        let half = self.len() / 2;
+        let (first, second) = self.split_at(half);
+        if second.len() >= 2 {
+            res.push(first.to_vec());
+        }
+        if first.len() >= 2 && first != second {
+            res.push(second.to_vec());
+        }
+        if self.len() > 1 {
+            for ix in 0..self.len() {
+                // removing either of two equal neighbours leaves the same vector.
+                if ix > 0 && self[ix] == self[ix - 1] {
+                    continue;
+                }
+                let mut smaller = self.clone();
+                smaller.remove(ix);
+                res.push(smaller);
+            }
+        }
+        for (ix, item) in self.iter().enumerate() {
+            for candidate in item.shrink() {
+                let mut simpler = self.clone();
+                simpler[ix] = candidate;
+                res.push(simpler);
+            }
+        }
+        res
+    }
+}
+
+impl Shrink for String {
+    fn shrink(&self) -> Vec<Self> {
+        let chars: Vec<char> = self.chars().collect();
+        chars
+            .shrink()
+            .into_iter()
+            .map(|c| c.into_iter().collect())
+            .collect()
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b) = self;
+        let mut res: Vec<Self> = a.shrink().into_iter().map(|a| (a, b.clone())).collect();
+        res.extend(b.shrink().into_iter().map(|b| (a.clone(), b)));
+        res
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone, C: Shrink + Clone> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b, c) = self;
+        let mut res: Vec<Self> = a
+            .shrink()
+            .into_iter()
+            .map(|a| (a, b.clone(), c.clone()))
+            .collect();
+        res.extend(b.shrink().into_iter().map(|b| (a.clone(), b, c.clone())));
+        res.extend(c.shrink().into_iter().map(|c| (a.clone(), b.clone(), c)));
+        res
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// How many generated inputs are tried.
+    pub cases: u32,
+    pub seed: u64,
+    /// How many shrunk candidates are tried before settling for the smallest failure so far.
+    pub max_shrinks: u32,
+}
+
+impl Config {
+    /// The defaults for the property called `name`, unless `AOC_PROPERTY_CASES` or
+    /// `AOC_PROPERTY_SEED` say otherwise.
+    pub fn for_property(name: &str) -> Self {
+        let from_env = |key: &str| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        Config {
+            cases: from_env("AOC_PROPERTY_CASES").map_or(256, |c| c as u32),
+            seed: from_env("AOC_PROPERTY_SEED").unwrap_or_else(|| fnv1a(name.as_bytes())),
+            max_shrinks: 10_000,
+        }
+    }
+}
+
+/// A generated input that broke a property, and the smallest input found that still breaks it.
+#[derive(Debug)]
+pub struct Failure<I> {
+    pub seed: u64,
+    /// How many inputs passed before this one.
+    pub case: u32,
+    pub original: I,
+    pub minimal: I,
+    /// Why `minimal` fails.
+    pub message: String,
+    pub shrinks: u32,
+}
+
+impl<I: Debug> Display for Failure<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "property failed after {} passing cases (reproduce with AOC_PROPERTY_SEED={})",
+            self.case, self.seed
+        )?;
+        writeln!(f, "minimal input: {:?}", self.minimal)?;
+        writeln!(f, "{}", self.message)?;
+        write!(
+            f,
+            "original input: {:?} (shrunk in {} steps)",
+            self.original, self.shrinks
+        )
+    }
+}
+
+/// Runs `property` on one input, counting a panic as a failure.
+fn test<I>(property: &impl Fn(&I) -> Result<(), String>, input: &I) -> Result<(), String> {
+    panic::catch_unwind(AssertUnwindSafe(|| property(input))).unwrap_or_else(|payload| {
+        let msg = payload
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".into());
+        Err(format!("panicked: {msg}"))
+    })
+}
+
+/// Tries `property` on `config.cases` inputs from `generate`, and shrinks the first one that fails.
+///
+/// An input passes when `property` returns `Ok`. Inputs that a property does not apply to (e.g.
+/// ones a shrink step made invalid) should pass as well.
+pub fn run<I, G, P>(config: &Config, generate: G, property: P) -> Result<(), Failure<I>>
+where
+    I: Shrink + Clone + Debug,
+    G: Fn(&mut Rng) -> I,
+    P: Fn(&I) -> Result<(), String>,
+{
+    let mut rng = Rng::new(config.seed);
+    for case in 0..config.cases {
+        let input = generate(&mut rng);
+        let Err(message) = test(&property, &input) else {
+            continue;
+        };
+
+        let mut minimal = input.clone();
+        let mut message = message;
+        let mut shrinks = 0;
+        'shrinking: loop {
+            for candidate in minimal.shrink() {
+                if shrinks == config.max_shrinks {
+                    break 'shrinking;
+                }
+                shrinks += 1;
+                if let Err(m) = test(&property, &candidate) {
+                    minimal = candidate;
+                    message = m;
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+
+        return Err(Failure {
+            seed: config.seed,
+            case,
+            original: input,
+            minimal,
+            message,
+            shrinks,
+        });
+    }
+    Ok(())
+}
+
+/// Panics with the minimal failing input if `property` does not hold for the generated inputs.
+pub fn check<I, G, P>(name: &str, generate: G, property: P)
+where
+    I: Shrink + Clone + Debug,
+    G: Fn(&mut Rng) -> I,
+    P: Fn(&I) -> Result<(), String>,
+{
+    if let Err(failure) = run(&Config::for_property(name), generate, property) {
+        panic!("`{name}`: {failure}");
+    }
+}
+
+/// A differential test: `candidate` has to return the same as `reference` for every generated input.
+pub fn check_against<I, O, G, R, C>(name: &str, generate: G, reference: R, candidate: C)
+where
+    I: Shrink + Clone + Debug,
+    O: PartialEq + Debug,
+    G: Fn(&mut Rng) -> I,
+    R: Fn(&I) -> O,
+    C: Fn(&I) -> O,
+{
+    check(name, generate, |input| {
+        let (expected, actual) = (reference(input), candidate(input));
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(format!("expected {expected:?}, got {actual:?}"))
+        }
+    });
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{check_against, run, Config, Rng, Shrink};
+
+    fn config(seed: u64) -> Config {
+        Config {
+            cases: 500,
+            seed,
+            max_shrinks: 10_000,
+        }
+    }
+
+    #[test]
+    fn generates_within_bounds() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.int(-3..=3)));
+            assert!((2..=4).contains(&rng.vec(2..=4, |r| r.bool()).len()));
+        }
+        assert!((0..100).any(|_| rng.int(i64::MIN..=i64::MAX) < 0));
+        assert_eq!(Rng::new(7).next_u64(), Rng::new(7).next_u64());
+    }
+
+    #[test]
+    fn shrinks_towards_simpler_values() {
+        assert_eq!(10_u32.shrink(), vec![0, 5, 9]);
+        assert_eq!((-4_i64).shrink(), vec![0, 4, -2, -3]);
+        assert!(0_i64.shrink().is_empty());
+        assert_eq!(vec![3_u8].shrink(), vec![vec![], vec![0], vec![1], vec![2]]);
+        assert_eq!(
+            vec![1_u8, 1].shrink(),
+            vec![vec![], vec![1], vec![0, 1], vec![1, 0]]
+        );
+    }
+
+    #[test]
+    fn shrinks_to_distinct_candidates() {
+        let mut rng = Rng::new(7);
+        for _ in 0..200 {
+            let xs = rng.vec(0..=8, |r| r.int(0..=3));
+            let candidates = xs.shrink();
+            for (ix, c) in candidates.iter().enumerate() {
+                assert!(!candidates[..ix].contains(c), "{c:?} twice for {xs:?}");
+                assert_ne!(*c, xs);
+            }
+        }
+    }
+
+    #[test]
+    fn shrinks_failures_to_a_minimal_input() {
+        // fails as soon as the numbers add up to 100.
+        let failure = run(
+            &config(42),
+            |rng| rng.vec(0..=20, |r| r.int(0..=50)),
+            |xs: &Vec<i64>| {
+                if xs.iter().sum::<i64>() < 100 {
+                    Ok(())
+                } else {
+                    Err("too large".into())
+                }
+            },
+        )
+        .unwrap_err();
+        // a sum of exactly 100 without zeros, anything smaller passes.
+        assert_eq!(failure.minimal.iter().sum::<i64>(), 100);
+        assert!(failure.minimal.iter().all(|x| *x > 0));
+        assert!(failure.original.len() >= failure.minimal.len());
+        assert_eq!(failure.message, "too large");
+    }
+
+    #[test]
+    fn shrinks_panics_and_tuples() {
+        let failure = run(
+            &config(3),
+            |rng| (rng.int(0..=1000), rng.int(0..=1000)),
+            |(a, b): &(i64, i64)| {
+                assert!(a + b < 500, "sum out of range");
+                Ok(())
+            },
+        )
+        .unwrap_err();
+        assert_eq!(failure.minimal.0 + failure.minimal.1, 500);
+        assert!(failure.message.starts_with("panicked: sum out of range"));
+        let report = failure.to_string();
+        assert!(report.contains("AOC_PROPERTY_SEED=3"));
+    }
+
+    #[test]
+    fn passes_when_implementations_agree() {
+        check_against(
+            "sum of a range",
+            |rng| rng.int(0..=1000),
+            |n: &i64| (0..=*n).sum::<i64>(),
+            |n: &i64| n * (n + 1) / 2,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal input: 11")]
+    fn reports_disagreements() {
+        check_against(
+            "off by one",
+            // narrow enough that 11, the only input they disagree on, is drawn whatever the seed.
+            |rng| rng.int(0..=20),
+            |n: &i64| *n > 10,
+            |n: &i64| *n > 11,
+        );
+    }
+}
@@ -0,0 +1,250 @@
+/// The answer a solution part produced, independent of the type the part returned.
+use std::{env, fmt::Display, fs};
+
+use crate::{ocr, Day};
+
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Int(i128),
+    Text(String),
+    /// A multi-line drawing, usually of block letters.
+    Grid(String),
+}
+
+macro_rules! impl_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::Int(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(u8, u16, u32, u64, i8, i16, i32, i64, i128);
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Int(value as i128)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(value: isize) -> Self {
+        Answer::Int(value as i128)
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(value: u128) -> Self {
+        i128::try_from(value).map_or_else(|_| Answer::Text(value.to_string()), Answer::Int)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        if value.trim().contains('\n') {
+            Answer::Grid(value)
+        } else {
+            Answer::Text(value)
+        }
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::from(value.to_string())
+    }
+}
+
+impl From<char> for Answer {
+    fn from(value: char) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Text(s) | Answer::Grid(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// Reads `s` as an integer, allowing surrounding whitespace and `,`/`_` thousands separators.
+/// Separators only count as such in groups of three digits (`1,234,567`), so a list like
+/// `4,6,3,5,6` stays text.
+fn parse_int(s: &str) -> Option<i128> {
+    let s = s.trim();
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let is_digits = |g: &str| !g.is_empty() && g.chars().all(|c| c.is_ascii_digit());
+    let is_number = match [',', '_'].into_iter().find(|sep| digits.contains(*sep)) {
+        None => is_digits(digits),
+        Some(sep) => {
+            let mut groups = digits.split(sep);
+            groups.next().is_some_and(|g| is_digits(g) && g.len() <= 3)
+                && groups.all(|g| is_digits(g) && g.len() == 3)
+        }
+    };
+    if !is_number {
+        return None;
+    }
+    s.replace([',', '_'], "").parse().ok()
+}
+
+impl Answer {
+    /// Interprets a stored answer, e.g. one read from `data/answers`.
+    pub fn parse(s: &str) -> Self {
+        let trimmed = s.trim_matches(['\n', '\r']);
+        if let Some(n) = parse_int(trimmed) {
+            Answer::Int(n)
+        } else if trimmed.contains('\n') {
+            Answer::Grid(trimmed.to_string())
+        } else {
+            Answer::Text(trimmed.trim().to_string())
+        }
+    }
+
+    /// The form of the answer that is sent to the website: integers without separators, text
+    /// without surrounding whitespace and drawings as the letters they spell out.
+    pub fn submission(&self) -> String {
+        match self {
+            Answer::Int(n) => n.to_string(),
+            Answer::Text(s) => parse_int(s).map_or_else(|| s.trim().to_string(), |n| n.to_string()),
+            Answer::Grid(s) => ocr::decode(s).unwrap_or_else(|_| s.trim().to_string()),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Answer::Int(_) => "int",
+            Answer::Text(_) => "text",
+            Answer::Grid(_) => "grid",
+        }
+    }
+
+    /// Serialises the answer as a JSON object of the form `{"type": .., "value": .., "submission": ..}`.
+    pub fn to_json(&self) -> String {
+        let value = match self {
+            Answer::Int(n) => n.to_string(),
+            Answer::Text(s) | Answer::Grid(s) => json_string(s),
+        };
+        format!(
+            "{{\"type\":\"{}\",\"value\":{value},\"submission\":{}}}",
+            self.kind(),
+            json_string(&self.submission())
+        )
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Answer::Int(a), Answer::Int(b)) => a == b,
+            _ => self.submission() == other.submission(),
+        }
+    }
+}
+
+/// Quotes and escapes `s` as a JSON string.
+pub fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+#[must_use]
+pub fn get_answer_path(day: Day, part: u8) -> String {
+    format!("data/answers/{day}-{part}.txt")
+}
+
+/// Reads the stored (known correct) answer for a part, if there is one.
+pub fn read_expected(day: Day, part: u8) -> Option<Answer> {
+    let cwd = env::current_dir().ok()?;
+    let contents = fs::read_to_string(cwd.join(get_answer_path(day, part))).ok()?;
+    if contents.trim().is_empty() {
+        return None;
+    }
+    Some(Answer::parse(&contents))
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{json_string, Answer};
+
+    const GRID: &str = "#..#.###.\n#..#..#..\n####..#..\n#..#..#..\n#..#..#..\n#..#.###.";
+
+    #[test]
+    fn converts_from_solution_types() {
+        assert_eq!(Answer::from(42_u32), Answer::Int(42));
+        assert_eq!(Answer::from(-7_i64), Answer::Int(-7));
+        assert!(matches!(Answer::from(u128::MAX), Answer::Text(_)));
+        assert!(matches!(Answer::from("abc"), Answer::Text(_)));
+        assert!(matches!(Answer::from(GRID), Answer::Grid(_)));
+    }
+
+    #[test]
+    fn normalises_submissions() {
+        assert_eq!(Answer::from(" 1,234,567 ").submission(), "1234567");
+        assert_eq!(Answer::from("1_000").submission(), "1000");
+        assert_eq!(Answer::from("  abc\t").submission(), "abc");
+        assert_eq!(Answer::from("1,2,3a").submission(), "1,2,3a");
+        assert_eq!(Answer::from("1,234_567").submission(), "1,234_567");
+    }
+
+    #[test]
+    fn keeps_lists_as_text() {
+        assert!(matches!(Answer::from("4,6,3,5,6"), Answer::Text(_)));
+        assert_eq!(Answer::from("4,6,3,5,6").submission(), "4,6,3,5,6");
+        assert!(matches!(Answer::parse("4,6,3,5,6\n"), Answer::Text(s) if s == "4,6,3,5,6"));
+        assert_ne!(Answer::parse("4,6,3,5,6"), Answer::from(46356_u32));
+        assert_ne!(Answer::parse("12,34"), Answer::from(1234_u32));
+        assert_eq!(Answer::from(GRID).submission(), "HI");
+    }
+
+    #[test]
+    fn parses_stored_answers() {
+        assert_eq!(Answer::parse("1234\n"), Answer::Int(1234));
+        assert!(matches!(Answer::parse("HI\n"), Answer::Text(s) if s == "HI"));
+        assert!(matches!(
+            Answer::parse(&format!("{GRID}\n")),
+            Answer::Grid(_)
+        ));
+    }
+
+    #[test]
+    fn compares_typed_answers() {
+        assert_eq!(Answer::from(1234_u64), Answer::parse("1,234"));
+        assert_ne!(Answer::from(1234_u64), Answer::parse("1235"));
+        assert_eq!(Answer::from(GRID), Answer::parse("HI"));
+        assert_eq!(Answer::from("-0012"), Answer::Int(-12));
+    }
+
+    #[test]
+    fn serialises_to_json() {
+        assert_eq!(
+            Answer::from(42_u8).to_json(),
+            r#"{"type":"int","value":42,"submission":"42"}"#
+        );
+        assert_eq!(
+            Answer::from("a\"b").to_json(),
+            r#"{"type":"text","value":"a\"b","submission":"a\"b"}"#
+        );
+        assert_eq!(json_string("x\ny"), r#""x\ny""#);
+    }
+}
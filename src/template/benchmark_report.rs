@@ -0,0 +1,413 @@
+/// Module that writes the timings of `cargo time` to a file, as data for spreadsheets and
+/// dashboards (CSV, JSON) or as a chart (SVG, or an HTML page with the chart and a table).
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fs, io};
+
+use crate::template::answer::json_string;
+use crate::template::markup::escape;
+use crate::template::readme::write_atomic;
+use crate::template::readme_benchmarks::{PartStatus, Timings};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+    Html,
+    Svg,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+            ReportFormat::Html => "html",
+            ReportFormat::Svg => "svg",
+        }
+    }
+
+    /// Picks the format from the extension of `path`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            "html" => Ok(ReportFormat::Html),
+            "svg" => Ok(ReportFormat::Svg),
+            x => Err(format!(
+                "unknown report format `{x}`, expected csv, json, html or svg"
+            )),
+        }
+    }
+}
+
+/// Where and in which format `cargo time` writes its report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub format: ReportFormat,
+    pub out: PathBuf,
+}
+
+impl Report {
+    /// Fills in whichever of `--report` and `--out` was left out: the format follows the file
+    /// extension, and the file defaults to `data/reports/benchmarks.<format>`.
+    pub fn from_args(
+        format: Option<ReportFormat>,
+        out: Option<PathBuf>,
+    ) -> Result<Option<Self>, String> {
+        let format = match (format, &out) {
+            (Some(format), _) => format,
+            (None, Some(out)) => ReportFormat::from_path(out).ok_or_else(|| {
+                format!(
+                    "can not tell the report format from `{}`, pass --report <csv|json|html|svg>",
+                    out.display()
+                )
+            })?,
+            (None, None) => return Ok(None),
+        };
+        let out = out.unwrap_or_else(|| {
+            Path::new("data")
+                .join("reports")
+                .join(format!("benchmarks.{}", format.extension()))
+        });
+        Ok(Some(Report { format, out }))
+    }
+}
+
+fn parts(timing: &Timings) -> [(u8, &PartStatus); 2] {
+    [(1, &timing.part_1), (2, &timing.part_2)]
+}
+
+fn to_csv(timings: &[Timings]) -> String {
+    let mut res = String::from("day,part,status,timing,nanos\n");
+    for timing in timings {
+        for (part, status) in parts(timing) {
+            let _ = writeln!(
+                res,
+                "{},{part},{},{},{}",
+                timing.day.into_inner(),
+                status.status(),
+                status.timing().unwrap_or_default(),
+                status.nanos().map(|n| n.to_string()).unwrap_or_default()
+            );
+        }
+    }
+    res
+}
+
+fn to_json(timings: &[Timings], total_millis: f64) -> String {
+    let days: Vec<String> = timings
+        .iter()
+        .map(|timing| {
+            let parts: Vec<String> = parts(timing)
+                .iter()
+                .map(|(part, status)| {
+                    format!(
+                        "{{\"part\":{part},\"status\":\"{}\",\"timing\":{},\"nanos\":{}}}",
+                        status.status(),
+                        status.timing().map_or_else(|| "null".into(), json_string),
+                        status
+                            .nanos()
+                            .map_or_else(|| "null".into(), |n| n.to_string())
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"day\":{},\"parts\":[{}],\"total_nanos\":{}}}",
+                timing.day.into_inner(),
+                parts.join(","),
+                timing.total_nanos
+            )
+        })
+        .collect();
+    format!(
+        "{{\"days\":[{}],\"total_millis\":{total_millis}}}\n",
+        days.join(",")
+    )
+}
+
+/// `nanos` as a short duration, for axis labels that fall on powers of ten.
+fn format_nanos(nanos: f64) -> String {
+    let (value, unit) = match nanos {
+        n if n < 1e3 => (n, "ns"),
+        n if n < 1e6 => (n / 1e3, "µs"),
+        n if n < 1e9 => (n / 1e6, "ms"),
+        n => (n / 1e9, "s"),
+    };
+    format!("{}{unit}", value.round())
+}
+
+const LABEL_WIDTH: f64 = 110.0;
+const CHART_WIDTH: f64 = 600.0;
+const VALUE_WIDTH: f64 = 90.0;
+const ROW_HEIGHT: f64 = 18.0;
+const TOP: f64 = 30.0;
+const BOTTOM: f64 = 30.0;
+const PART_COLOURS: [&str; 2] = ["#4e79a7", "#f28e2b"];
+
+/// The decades the chart spans, so every bar starts at the same power of ten.
+fn log_range(timings: &[Timings]) -> (f64, f64) {
+    let nanos = timings
+        .iter()
+        .flat_map(|t| parts(t).map(|(_, s)| s.nanos()))
+        .flatten()
+        .filter(|n| *n > 0.0);
+    let (min, max) = nanos.fold((f64::INFINITY, 0_f64), |(lo, hi), n| (lo.min(n), hi.max(n)));
+    if min > max {
+        return (0.0, 1.0);
+    }
+    let lo = min.log10().floor();
+    let hi = max.log10().ceil().max(lo + 1.0);
+    (lo, hi)
+}
+
+/// A horizontal bar per part, scaled logarithmically so nanosecond and second timings both show.
+fn to_svg(timings: &[Timings], total_millis: f64) -> String {
+    let (lo, hi) = log_range(timings);
+    let x = |nanos: f64| LABEL_WIDTH + (nanos.log10() - lo).max(0.0) / (hi - lo) * CHART_WIDTH;
+
+    let rows = timings.len() * 2;
+    let width = LABEL_WIDTH + CHART_WIDTH + VALUE_WIDTH;
+    let height = TOP + rows as f64 * ROW_HEIGHT + BOTTOM;
+    let axis_y = TOP + rows as f64 * ROW_HEIGHT;
+
+    let mut res = String::new();
+    let _ = writeln!(
+        res,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+        viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"11\">"
+    );
+    let _ = writeln!(
+        res,
+        "  <text x=\"{LABEL_WIDTH}\" y=\"18\" font-size=\"14\" font-weight=\"bold\">Benchmarks (total {total_millis:.2}ms, log scale)</text>"
+    );
+
+    for decade in (lo as i32)..=(hi as i32) {
+        let nanos = 10_f64.powi(decade);
+        let gx = x(nanos);
+        let _ = writeln!(
+            res,
+            "  <line x1=\"{gx:.1}\" y1=\"{TOP}\" x2=\"{gx:.1}\" y2=\"{axis_y}\" stroke=\"#ddd\"/>"
+        );
+        let _ = writeln!(
+            res,
+            "  <text x=\"{gx:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            axis_y + 15.0,
+            format_nanos(nanos)
+        );
+    }
+
+    for (ix, (timing, (part, status))) in timings
+        .iter()
+        .flat_map(|t| parts(t).map(|p| (t, p)))
+        .enumerate()
+    {
+        let y = TOP + ix as f64 * ROW_HEIGHT;
+        let text_y = y + ROW_HEIGHT / 2.0 + 4.0;
+        let _ = writeln!(
+            res,
+            "  <text x=\"{:.1}\" y=\"{text_y:.1}\" text-anchor=\"end\">Day {} · Part {part}</text>",
+            LABEL_WIDTH - 6.0,
+            timing.day.into_inner()
+        );
+        match status.nanos() {
+            Some(nanos) => {
+                let end = x(nanos);
+                let _ = writeln!(
+                    res,
+                    "  <rect x=\"{LABEL_WIDTH}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}</title></rect>",
+                    y + 3.0,
+                    end - LABEL_WIDTH,
+                    ROW_HEIGHT - 6.0,
+                    PART_COLOURS[usize::from(part - 1)],
+                    escape(status.timing().unwrap_or_default())
+                );
+                let _ = writeln!(
+                    res,
+                    "  <text x=\"{:.1}\" y=\"{text_y:.1}\">{}</text>",
+                    end + 4.0,
+                    escape(status.timing().unwrap_or_default())
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    res,
+                    "  <text x=\"{:.1}\" y=\"{text_y:.1}\" fill=\"#999\">{}</text>",
+                    LABEL_WIDTH + 4.0,
+                    status.status().replace('_', " ")
+                );
+            }
+        }
+    }
+
+    res.push_str("</svg>\n");
+    res
+}
+
+/// A page that needs nothing but itself: the chart inline, followed by a table of the timings.
+fn to_html(timings: &[Timings], total_millis: f64) -> String {
+    let mut rows = String::new();
+    for timing in timings {
+        let cell = |status: &PartStatus| {
+            escape(
+                status
+                    .timing()
+                    .unwrap_or(&status.status().replace('_', " ")),
+            )
+        };
+        let _ = writeln!(
+            rows,
+            "      <tr><td>Day {}</td><td>{}</td><td>{}</td></tr>",
+            timing.day.into_inner(),
+            cell(&timing.part_1),
+            cell(&timing.part_2)
+        );
+    }
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+  <head>
+    <meta charset=\"utf-8\">
+    <title>Advent of Code benchmarks</title>
+    <style>
+      body {{ font-family: sans-serif; margin: 2em; }}
+      table {{ border-collapse: collapse; margin-top: 1em; }}
+      th, td {{ border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: center; }}
+    </style>
+  </head>
+  <body>
+    <h1>Benchmarks</h1>
+{}    <table>
+      <tr><th>Day</th><th>Part 1</th><th>Part 2</th></tr>
+{rows}    </table>
+    <p><strong>Total: {total_millis:.2}ms</strong></p>
+  </body>
+</html>
+",
+        to_svg(timings, total_millis)
+    )
+}
+
+#[must_use]
+pub fn render(format: ReportFormat, timings: &[Timings], total_millis: f64) -> String {
+    match format {
+        ReportFormat::Csv => to_csv(timings),
+        ReportFormat::Json => to_json(timings, total_millis),
+        ReportFormat::Html => to_html(timings, total_millis),
+        ReportFormat::Svg => to_svg(timings, total_millis),
+    }
+}
+
+pub fn write(report: &Report, timings: &[Timings], total_millis: f64) -> io::Result<()> {
+    if let Some(dir) = report.out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    write_atomic(&report.out, &render(report.format, timings, total_millis))
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{render, Report, ReportFormat};
+    use crate::day;
+    use crate::template::readme_benchmarks::{PartStatus, Timings};
+    use std::path::PathBuf;
+
+    fn get_mock_timings() -> Vec<Timings> {
+        vec![
+            Timings {
+                day: day!(1),
+                part_1: PartStatus::Solved(Some("10.0µs".into())),
+                part_2: PartStatus::Solved(Some("2.5ms".into())),
+                total_nanos: 2_510_000.0,
+            },
+            Timings {
+                day: day!(2),
+                part_1: PartStatus::Solved(Some("1.2s".into())),
+                part_2: PartStatus::Failed,
+                total_nanos: 1_200_000_000.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!("SVG".parse(), Ok(ReportFormat::Svg));
+        assert!("pdf".parse::<ReportFormat>().is_err());
+        assert_eq!(
+            Report::from_args(None, Some(PathBuf::from("out/times.csv"))),
+            Ok(Some(Report {
+                format: ReportFormat::Csv,
+                out: PathBuf::from("out/times.csv")
+            }))
+        );
+        assert_eq!(
+            Report::from_args(Some(ReportFormat::Json), None),
+            Ok(Some(Report {
+                format: ReportFormat::Json,
+                out: PathBuf::from("data/reports/benchmarks.json")
+            }))
+        );
+        assert!(Report::from_args(None, Some(PathBuf::from("times"))).is_err());
+        assert_eq!(Report::from_args(None, None), Ok(None));
+    }
+
+    #[test]
+    fn renders_csv() {
+        let csv = render(ReportFormat::Csv, &get_mock_timings(), 1202.51);
+        let expected = [
+            "day,part,status,timing,nanos",
+            "1,1,solved,10.0µs,10000",
+            "1,2,solved,2.5ms,2500000",
+            "2,1,solved,1.2s,1200000000",
+            "2,2,failed,,",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(csv, expected);
+    }
+
+    #[test]
+    fn renders_json() {
+        let json = render(ReportFormat::Json, &get_mock_timings()[1..], 1200.0);
+        assert_eq!(
+            json,
+            "{\"days\":[{\"day\":2,\"parts\":[\
+            {\"part\":1,\"status\":\"solved\",\"timing\":\"1.2s\",\"nanos\":1200000000},\
+            {\"part\":2,\"status\":\"failed\",\"timing\":null,\"nanos\":null}],\
+            \"total_nanos\":1200000000}],\"total_millis\":1200}\n"
+        );
+    }
+
+    #[test]
+    fn renders_svg_on_a_log_scale() {
+        let svg = render(ReportFormat::Svg, &get_mock_timings(), 1202.51);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        // 10µs to 1.2s spans the decades from 10µs to 10s, 6 decades of 100px each.
+        assert!(svg.contains(">10µs</text>"));
+        assert!(svg.contains(">10s</text>"));
+        assert!(svg.contains("width=\"0.0\" height=\"12.0\" fill=\"#4e79a7\""));
+        assert!(svg.contains("width=\"239.8\" height=\"12.0\" fill=\"#f28e2b\""));
+        assert!(svg.contains(">Day 2 · Part 2</text>"));
+        assert!(svg.contains(">failed</text>"));
+        assert_eq!(svg.matches("<rect").count(), 3);
+    }
+
+    #[test]
+    fn renders_html() {
+        let html = render(ReportFormat::Html, &get_mock_timings(), 1202.51);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<svg xmlns"));
+        assert!(html.contains("<tr><td>Day 2</td><td>1.2s</td><td>failed</td></tr>"));
+        assert!(html.contains("Total: 1202.51ms"));
+    }
+}
@@ -1,12 +1,13 @@
 use std::io;
 
 use crate::template::{
-    readme_benchmarks::{self, Timings},
+    benchmark_report::{self, Report},
+    readme_benchmarks::{self, PartStatus, Timings},
     ANSI_BOLD, ANSI_ITALIC, ANSI_RESET,
 };
 use crate::{all_days, Day};
 
-pub fn handle(is_release: bool, is_timed: bool) {
+pub fn handle(is_release: bool, is_timed: bool, timeout: Option<f64>, report: Option<Report>) {
     let mut timings: Vec<Timings> = vec![];
 
     all_days().for_each(|day| {
@@ -17,7 +18,7 @@ pub fn handle(is_release: bool, is_timed: bool) {
         println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
         println!("------");
 
-        let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+        let output = child_commands::run_solution(day, is_timed, is_release, timeout).unwrap();
 
         if output.is_empty() {
             println!("Not solved.");
@@ -27,11 +28,27 @@ pub fn handle(is_release: bool, is_timed: bool) {
         }
     });
 
+    print_summary(&timings);
+
+    if report.is_some() && !is_timed {
+        eprintln!("A benchmark report needs timings, pass --time or use `cargo time`.");
+    }
+
     if is_timed {
         let total_millis = timings.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64;
 
         println!("\n{ANSI_BOLD}Total:{ANSI_RESET} {ANSI_ITALIC}{total_millis:.2}ms{ANSI_RESET}");
 
+        if let Some(report) = &report {
+            match benchmark_report::write(report, &timings, total_millis) {
+                Ok(()) => println!("Wrote the benchmark report to {}.", report.out.display()),
+                Err(e) => eprintln!(
+                    "Failed to write the benchmark report to {}: {e}",
+                    report.out.display()
+                ),
+            }
+        }
+
         if is_release {
             match readme_benchmarks::update(timings, total_millis) {
                 Ok(()) => println!("Successfully updated README with benchmarks."),
@@ -43,6 +60,35 @@ pub fn handle(is_release: bool, is_timed: bool) {
     }
 }
 
+fn print_summary(timings: &[Timings]) {
+    let statuses = timings
+        .iter()
+        .flat_map(|t| [(t.day, 1, &t.part_1), (t.day, 2, &t.part_2)]);
+
+    let mut solved = 0;
+    let mut not_implemented = 0;
+    let mut failed = vec![];
+
+    for (day, part, status) in statuses {
+        match status {
+            PartStatus::Solved(_) => solved += 1,
+            PartStatus::NotImplemented => not_implemented += 1,
+            PartStatus::Failed => failed.push(format!("Day {day} part {part} failed")),
+            PartStatus::Panicked => failed.push(format!("Day {day} part {part} panicked")),
+            PartStatus::TimedOut => failed.push(format!("Day {day} part {part} timed out")),
+        }
+    }
+
+    println!(
+        "\n{ANSI_BOLD}Summary:{ANSI_RESET} {solved} solved, {not_implemented} not implemented, {} failed",
+        failed.len()
+    );
+
+    for f in failed {
+        println!("  ✖ {f}");
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     BrokenPipe,
@@ -65,6 +111,7 @@ pub fn get_path_for_bin(day: Day) -> String {
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
 mod child_commands {
     use super::{get_path_for_bin, Error};
+    use crate::template::readme_benchmarks::{parse_timing, PartStatus};
     use crate::Day;
     use std::{
         io::{BufRead, BufReader},
@@ -74,25 +121,37 @@ mod child_commands {
     };
 
     /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    pub fn run_solution(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        timeout: Option<f64>,
+    ) -> Result<Vec<String>, Error> {
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
             return Ok(vec![]);
         }
 
         let day_padded = day.to_string();
+        let timeout_str = timeout.map(|t| t.to_string());
         let mut args = vec!["run", "--quiet", "--bin", &day_padded];
 
         if is_release {
             args.push("--release");
         }
 
+        args.push("--");
+
         if is_timed {
             // mirror `--time` flag to child invocations.
-            args.push("--");
             args.push("--time");
         }
 
+        if let Some(timeout) = &timeout_str {
+            args.push("--timeout");
+            args.push(timeout);
+        }
+
         // spawn child command with piped stdout/stderr.
         // forward output to stdout/stderr while grabbing stdout lines.
 
@@ -128,31 +187,48 @@ mod child_commands {
     pub fn parse_exec_time(output: &[String], day: Day) -> super::Timings {
         let mut timings = super::Timings {
             day,
-            part_1: None,
-            part_2: None,
+            part_1: PartStatus::NotImplemented,
+            part_2: PartStatus::NotImplemented,
             total_nanos: 0_f64,
         };
 
         output
             .iter()
             .filter_map(|l| {
-                if !l.contains(" samples)") {
+                let (part, rest) = l.split_once(':')?;
+                if !part.starts_with("Part ") {
                     return None;
                 }
 
+                let rest = rest.trim();
+                if rest.starts_with("✖ failed") {
+                    return Some((part, PartStatus::Failed, 0_f64));
+                }
+                if rest.starts_with("✖ panicked") {
+                    return Some((part, PartStatus::Panicked, 0_f64));
+                }
+                if rest.starts_with("✖ timed out") {
+                    return Some((part, PartStatus::TimedOut, 0_f64));
+                }
+                if rest.starts_with('✖') {
+                    return Some((part, PartStatus::NotImplemented, 0_f64));
+                }
+                if !l.contains(" samples)") {
+                    return Some((part, PartStatus::Solved(None), 0_f64));
+                }
+
                 let Some((timing_str, nanos)) = parse_time(l) else {
                     eprintln!("Could not parse timings from line: {l}");
-                    return None;
+                    return Some((part, PartStatus::Solved(None), 0_f64));
                 };
 
-                let part = l.split(':').next()?;
-                Some((part, timing_str, nanos))
+                Some((part, PartStatus::Solved(Some(timing_str.into())), nanos))
             })
-            .for_each(|(part, timing_str, nanos)| {
+            .for_each(|(part, status, nanos)| {
                 if part.contains("Part 1") {
-                    timings.part_1 = Some(timing_str.into());
+                    timings.part_1 = status;
                 } else if part.contains("Part 2") {
-                    timings.part_2 = Some(timing_str.into());
+                    timings.part_2 = status;
                 }
 
                 timings.total_nanos += nanos;
@@ -161,29 +237,17 @@ mod child_commands {
         timings
     }
 
-    fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
-        s.split(postfix).next()?.parse().ok()
-    }
-
     fn parse_time(line: &str) -> Option<(&str, f64)> {
-        // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
         let str_timing = line
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
 
-        let parsed_timing = match str_timing {
-            s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
-            s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1000_f64),
-            s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
-            s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
-        }?;
-
-        Some((str_timing, parsed_timing))
+        Some((str_timing, parse_timing(str_timing)?))
     }
 
     /// copied from: https://github.com/rust-lang/rust/blob/1.64.0/library/std/src/macros.rs#L328-L333
@@ -205,6 +269,7 @@ mod child_commands {
         use super::parse_exec_time;
 
         use crate::day;
+        use crate::template::readme_benchmarks::PartStatus;
 
         #[test]
         fn test_well_formed() {
@@ -217,8 +282,8 @@ mod child_commands {
                 day!(1),
             );
             assert_approx_eq!(res.total_nanos, 74130074.13_f64);
-            assert_eq!(res.part_1.unwrap(), "74.13ns");
-            assert_eq!(res.part_2.unwrap(), "74.13ms");
+            assert_eq!(res.part_1, PartStatus::Solved(Some("74.13ns".into())));
+            assert_eq!(res.part_2, PartStatus::Solved(Some("74.13ms".into())));
         }
 
         #[test]
@@ -232,8 +297,8 @@ mod child_commands {
                 day!(1),
             );
             assert_approx_eq!(res.total_nanos, 2100000000_f64);
-            assert_eq!(res.part_1.unwrap(), "2s");
-            assert_eq!(res.part_2.unwrap(), "100ms");
+            assert_eq!(res.part_1, PartStatus::Solved(Some("2s".into())));
+            assert_eq!(res.part_2, PartStatus::Solved(Some("100ms".into())));
         }
 
         #[test]
@@ -247,8 +312,37 @@ mod child_commands {
                 day!(1),
             );
             assert_approx_eq!(res.total_nanos, 0_f64);
-            assert_eq!(res.part_1.is_none(), true);
-            assert_eq!(res.part_2.is_none(), true);
+            assert_eq!(res.part_1, PartStatus::NotImplemented);
+            assert_eq!(res.part_2, PartStatus::NotImplemented);
+        }
+
+        #[test]
+        fn test_failed_parts() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 42 (1.0ms)".into(),
+                    "Part 2: ✖ failed: line 3: unknown color `pink`".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            assert_approx_eq!(res.total_nanos, 0_f64);
+            assert_eq!(res.part_1, PartStatus::Solved(None));
+            assert_eq!(res.part_2, PartStatus::Failed);
+        }
+
+        #[test]
+        fn test_panicked_and_timed_out_parts() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: ✖ panicked: attempt to subtract with overflow".into(),
+                    "Part 2: ✖ timed out after 10.0s".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            assert_eq!(res.part_1, PartStatus::Panicked);
+            assert_eq!(res.part_2, PartStatus::TimedOut);
         }
     }
 }
@@ -0,0 +1,26 @@
+use std::process::{self, Command};
+
+use crate::Day;
+
+/// Runs the day's binary with `--check-input`, which lints its input and runs its schema.
+pub fn handle(day: Day) {
+    let day_padded = day.to_string();
+    let args = [
+        "run",
+        "--quiet",
+        "--release",
+        "--bin",
+        &day_padded,
+        "--",
+        "--check-input",
+    ];
+
+    match Command::new("cargo").args(args).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("Could not run day {day}: {e}");
+            process::exit(1);
+        }
+    }
+}
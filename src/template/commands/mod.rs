@@ -1,5 +1,7 @@
 pub mod all;
+pub mod check_input;
 pub mod download;
 pub mod read;
 pub mod scaffold;
 pub mod solve;
+pub mod stars;
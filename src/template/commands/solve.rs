@@ -2,19 +2,104 @@ use std::process::{Command, Stdio};
 
 use crate::Day;
 
-pub fn handle(day: Day, release: bool, time: bool, submit_part: Option<u8>, dhat: bool) {
+/// Flags that are passed through to the solution binary and read by its runner.
+#[derive(Debug, Default)]
+pub struct RunnerFlags {
+    pub time: bool,
+    pub json: bool,
+    pub timeout: Option<f64>,
+    pub explain: bool,
+    pub variant: Option<String>,
+    pub compare: bool,
+    pub fuzz: bool,
+    pub runs: Option<u32>,
+    pub seed: Option<u64>,
+    pub viz: bool,
+    pub viz_format: Option<String>,
+    pub gif: bool,
+    pub profile_cpu: bool,
+}
+
+impl RunnerFlags {
+    fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if self.time {
+            args.push("--time".to_string());
+        }
+        if self.json {
+            args.push("--json".to_string());
+        }
+        if let Some(timeout) = self.timeout {
+            args.push("--timeout".to_string());
+            args.push(timeout.to_string());
+        }
+        if self.explain {
+            args.push("--explain".to_string());
+        }
+        if let Some(variant) = &self.variant {
+            args.push("--variant".to_string());
+            args.push(variant.clone());
+        }
+        if self.compare {
+            args.push("--compare".to_string());
+        }
+        if self.fuzz {
+            args.push("--fuzz".to_string());
+        }
+        if let Some(runs) = self.runs {
+            args.push("--runs".to_string());
+            args.push(runs.to_string());
+        }
+        if let Some(seed) = self.seed {
+            args.push("--seed".to_string());
+            args.push(seed.to_string());
+        }
+        if self.viz {
+            args.push("--viz".to_string());
+        }
+        if let Some(format) = &self.viz_format {
+            args.push("--viz-format".to_string());
+            args.push(format.clone());
+        }
+        if self.gif {
+            args.push("--gif".to_string());
+        }
+        if self.profile_cpu {
+            args.push("--profile-cpu".to_string());
+        }
+        args
+    }
+}
+
+pub fn handle(
+    day: Day,
+    release: bool,
+    submit_part: Option<u8>,
+    dhat: bool,
+    trace: bool,
+    flags: &RunnerFlags,
+) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
+    let mut features = vec![];
     if dhat {
-        cmd_args.extend([
-            "--profile".to_string(),
-            "dhat".to_string(),
-            "--features".to_string(),
-            "dhat-heap".to_string(),
-        ]);
+        cmd_args.extend(["--profile".to_string(), "dhat".to_string()]);
+        features.push("dhat-heap");
+    } else if flags.profile_cpu {
+        // profiles are only worth reading for optimised code, with symbols to name the frames.
+        cmd_args.extend(["--profile".to_string(), "profiling".to_string()]);
     } else if release {
         cmd_args.push("--release".to_string());
     }
+    if trace {
+        features.push("trace");
+    }
+    if flags.profile_cpu {
+        features.push("profile-cpu");
+    }
+    if !features.is_empty() {
+        cmd_args.extend(["--features".to_string(), features.join(",")]);
+    }
 
     cmd_args.push("--".to_string());
 
@@ -23,9 +108,7 @@ pub fn handle(day: Day, release: bool, time: bool, submit_part: Option<u8>, dhat
         cmd_args.push(submit_part.to_string());
     }
 
-    if time {
-        cmd_args.push("--time".to_string());
-    }
+    cmd_args.extend(flags.to_args());
 
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
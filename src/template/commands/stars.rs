@@ -0,0 +1,183 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::template::readme_stars::{self, Progress};
+use crate::template::{aoc_cli, ANSI_BOLD, ANSI_RESET};
+use crate::{all_days, Day};
+
+use super::all::get_path_for_bin;
+
+/// Works out star progress offline: a part earns its star once it returns an answer that matches
+/// the one stored in `data/answers`, or, without a stored answer, once it returns an answer at all.
+/// A day that fails to build or run keeps the row the readme already shows for it, and the
+/// progress table is then rewritten.
+pub fn handle(timeout: Option<f64>) {
+    let shown = readme_stars::read();
+    let mut progress: Vec<Progress> = vec![];
+
+    for day in all_days() {
+        // days that have not been scaffolded yet have no row.
+        if !Path::new(&get_path_for_bin(day)).exists() {
+            continue;
+        }
+
+        let output = match run_solution(day, timeout) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Day {day}: could not run the solution: {e}");
+                String::new()
+            }
+        };
+
+        let p = keep_shown(parse_progress(&output, day), day, &shown);
+        println!(
+            "{ANSI_BOLD}Day {day}{ANSI_RESET}: {}",
+            match p.stars() {
+                0 => "no stars".into(),
+                n => "⭐".repeat(n),
+            }
+        );
+        progress.push(p);
+    }
+
+    // days that are starred in the readme but not scaffolded here keep their row.
+    for p in shown {
+        if !progress.iter().any(|q| q.day == p.day) {
+            progress.push(p);
+        }
+    }
+
+    let stars: usize = progress.iter().map(Progress::stars).sum();
+    println!("\n{ANSI_BOLD}Total:{ANSI_RESET} {stars} ⭐");
+
+    match readme_stars::update(aoc_cli::get_year(), &progress) {
+        Ok(()) => println!("Successfully updated README with star progress."),
+        Err(_) => eprintln!("Failed to update readme with star progress."),
+    }
+}
+
+/// Runs the day with `--json`, which reports whether each part matches its stored answer.
+fn run_solution(day: Day, timeout: Option<f64>) -> std::io::Result<String> {
+    let day_padded = day.to_string();
+    let timeout_str = timeout.map(|t| t.to_string());
+    let mut args = vec!["run", "--quiet", "--release", "--bin", &day_padded, "--"];
+    args.push("--json");
+    if let Some(timeout) = &timeout_str {
+        args.push("--timeout");
+        args.push(timeout);
+    }
+
+    let output = Command::new("cargo").args(&args).output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Reads whether a part earned its star from its JSON line: its answer matches the stored one
+/// (`"correct":true`), or it was solved and there is no stored answer to check (`"correct":null`).
+/// Only the runner's own output is read here, so the fields are found by name rather than by
+/// parsing the whole object.
+fn parse_part(line: &str) -> Option<(u8, bool)> {
+    // `day`, `part` and `status` come first and `correct` last, answers and errors may contain
+    // anything.
+    let mut fields = line.strip_prefix('{')?.split(',');
+    let part = fields
+        .find_map(|f| f.strip_prefix("\"part\":"))?
+        .parse()
+        .ok()?;
+    let solved = fields.next()? == "\"status\":\"solved\"";
+    let (_, correct) = line.rsplit_once("\"correct\":")?;
+    let earned = match correct.trim_end().trim_end_matches('}') {
+        "true" => true,
+        "null" => solved,
+        _ => false,
+    };
+    Some((part, earned))
+}
+
+/// The stars the day earned in this run, or `None` if it did not report any part, i.e. it failed
+/// to build or run.
+fn parse_progress(output: &str, day: Day) -> Option<Progress> {
+    let mut progress = Progress {
+        day,
+        part_1: false,
+        part_2: false,
+    };
+    let mut ran = false;
+    for (part, earned) in output.lines().filter_map(parse_part) {
+        ran = true;
+        match part {
+            1 => progress.part_1 = earned,
+            2 => progress.part_2 = earned,
+            _ => {}
+        }
+    }
+    ran.then_some(progress)
+}
+
+/// A day that did not run says nothing about its stars, so it keeps the row the readme shows for
+/// it. A day that ran only has the stars it earned, a wrong answer loses its star.
+fn keep_shown(progress: Option<Progress>, day: Day, shown: &[Progress]) -> Progress {
+    progress
+        .or_else(|| shown.iter().find(|s| s.day == day).cloned())
+        .unwrap_or(Progress {
+            day,
+            part_1: false,
+            part_2: false,
+        })
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{keep_shown, parse_progress};
+    use crate::day;
+    use crate::template::readme_stars::Progress;
+
+    #[test]
+    fn stars_only_verified_parts() {
+        let output = [
+            r#"{"day":5,"part":1,"status":"solved","answer":{"type":"int","value":35,"submission":"35"},"error":null,"nanos":1,"samples":1,"correct":true}"#,
+            r#"{"day":5,"part":2,"status":"solved","answer":{"type":"int","value":47,"submission":"47"},"error":null,"nanos":1,"samples":1,"correct":false}"#,
+        ]
+        .join("\n");
+        let progress = parse_progress(&output, day!(5)).unwrap();
+        assert!(progress.part_1);
+        assert!(!progress.part_2);
+    }
+
+    #[test]
+    fn stars_solved_parts_without_stored_answers() {
+        let output = [
+            r#"{"day":10,"part":1,"status":"solved","answer":{"type":"text","value":"a,\"correct\":true","submission":"a"},"error":null,"nanos":1,"samples":1,"correct":null}"#,
+            r#"{"day":10,"part":2,"status":"not_implemented","answer":null,"error":null,"nanos":0,"samples":1,"correct":null}"#,
+        ]
+        .join("\n");
+        let progress = parse_progress(&output, day!(10)).unwrap();
+        assert!(progress.part_1);
+        assert!(!progress.part_2);
+
+        let failed = r#"{"day":10,"part":1,"status":"failed","answer":null,"error":"bad, \"status\":\"solved\"","nanos":0,"samples":1,"correct":null}"#;
+        assert_eq!(parse_progress(failed, day!(10)).unwrap().stars(), 0);
+        assert_eq!(parse_progress("", day!(10)), None);
+    }
+
+    #[test]
+    fn keeps_the_readme_row_of_days_that_did_not_run() {
+        let shown = [Progress {
+            day: day!(3),
+            part_1: true,
+            part_2: true,
+        }];
+        let progress = keep_shown(parse_progress("", day!(3)), day!(3), &shown);
+        assert_eq!(progress.stars(), 2);
+        let progress = keep_shown(parse_progress("", day!(4)), day!(4), &shown);
+        assert_eq!(progress.stars(), 0);
+
+        let wrong = [
+            r#"{"day":3,"part":1,"status":"solved","answer":{"type":"int","value":4361,"submission":"4361"},"error":null,"nanos":1,"samples":1,"correct":true}"#,
+            r#"{"day":3,"part":2,"status":"solved","answer":{"type":"int","value":1,"submission":"1"},"error":null,"nanos":1,"samples":1,"correct":false}"#,
+        ]
+        .join("\n");
+        let progress = keep_shown(parse_progress(&wrong, day!(3)), day!(3), &shown);
+        assert!(progress.part_1);
+        assert!(!progress.part_2);
+    }
+}
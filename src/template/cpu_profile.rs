@@ -0,0 +1,563 @@
+/// Samples where a part spends its CPU time and draws the samples as a flamegraph.
+///
+/// `cargo solve <day> --profile-cpu` builds the day with the `profile-cpu` feature and, after each
+/// part is solved, runs the part again in a loop. Every few milliseconds of CPU time the process
+/// gets a `SIGPROF`, and the signal handler records the call stack it interrupted. Only the thread
+/// running the loop is sampled, work the part hands to other threads (e.g. `rayon`) does not show
+/// up. The stacks are written to `data/profiles/DD-pP.folded`, one line per stack with how often it
+/// was seen, and drawn as a flamegraph to `data/profiles/DD-pP.svg`. Sampling needs a Unix system.
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::property::fnv1a;
+use crate::template::markup::escape;
+use crate::Day;
+
+/// How often each stack was sampled, keyed by its frames, outermost first.
+pub type Folded = BTreeMap<Vec<String>, u64>;
+
+/// What a profile of a part found, and where it was written.
+pub struct Summary {
+    pub runs: u128,
+    pub samples: u64,
+    pub svg: PathBuf,
+    pub folded: PathBuf,
+}
+
+/// `--profile-cpu` profiles every part that solves, after it has run.
+pub fn is_profile_cpu() -> bool {
+    env::args().any(|x| x == "--profile-cpu")
+}
+
+#[must_use]
+pub fn get_profile_dir() -> PathBuf {
+    Path::new("data").join("profiles")
+}
+
+/// Counts how often each stack occurs.
+pub fn fold(stacks: impl IntoIterator<Item = Vec<String>>) -> Folded {
+    let mut folded = Folded::new();
+    for stack in stacks {
+        *folded.entry(stack).or_default() += 1;
+    }
+    folded
+}
+
+/// The folded stack format read by `flamegraph.pl`, `inferno` and speedscope: the frames of a
+/// stack joined by `;`, then how often it was sampled.
+#[must_use]
+pub fn to_folded(folded: &Folded) -> String {
+    let mut res = String::new();
+    for (stack, count) in folded {
+        // `;` separates frames, so it cannot appear in one, e.g. in `[u8; 4]`.
+        let frames: Vec<String> = stack.iter().map(|f| f.replace(';', ",")).collect();
+        let _ = writeln!(res, "{} {count}", frames.join(";"));
+    }
+    res
+}
+
+const WIDTH: f64 = 1200.0;
+const FRAME_HEIGHT: f64 = 16.0;
+const TOP: f64 = 30.0;
+const BOTTOM: f64 = 10.0;
+const CHAR_WIDTH: f64 = 7.0;
+
+/// Draws `folded` as a flamegraph: the whole profile at the bottom, and on top of each frame the
+/// frames it called, each as wide as its share of the samples. Callees are ordered by name, so
+/// the same call tree always looks the same. Hovering a frame shows its full name and samples.
+#[must_use]
+pub fn to_svg(folded: &Folded, title: &str) -> String {
+    let total: u64 = folded.values().sum();
+    let depth = folded.keys().map(Vec::len).max().unwrap_or(0);
+    let height = TOP + (depth + 1) as f64 * FRAME_HEIGHT + BOTTOM;
+
+    let mut res = String::new();
+    let _ = writeln!(
+        res,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" \
+        viewBox=\"0 0 {WIDTH} {height}\" font-family=\"monospace\" font-size=\"12\">"
+    );
+    let _ = writeln!(
+        res,
+        "  <text x=\"{:.1}\" y=\"20\" font-size=\"14\" font-weight=\"bold\" text-anchor=\"middle\">{}</text>",
+        WIDTH / 2.0,
+        escape(title)
+    );
+    if total == 0 {
+        res.push_str("</svg>\n");
+        return res;
+    }
+
+    let frame = Frame {
+        total,
+        scale: WIDTH / total as f64,
+        bottom: height - BOTTOM,
+    };
+    frame.draw("all", total, 0, 0.0, &mut res);
+    let stacks: Vec<(&Vec<String>, u64)> = folded.iter().map(|(s, c)| (s, *c)).collect();
+    frame.draw_callees(&stacks, 0, 0.0, &mut res);
+
+    res.push_str("</svg>\n");
+    res
+}
+
+struct Frame {
+    total: u64,
+    scale: f64,
+    bottom: f64,
+}
+
+impl Frame {
+    /// Draws the frames at `depth` of `stacks`, which share their first `depth` frames, and then
+    /// the frames they called. Time spent in the caller itself is left empty on the left.
+    fn draw_callees(&self, stacks: &[(&Vec<String>, u64)], depth: usize, x: f64, res: &mut String) {
+        let own: u64 = stacks
+            .iter()
+            .filter(|(s, _)| s.len() == depth)
+            .map(|(_, c)| c)
+            .sum();
+        let mut x = x + own as f64 * self.scale;
+
+        let callees: Vec<(&Vec<String>, u64)> = stacks
+            .iter()
+            .filter(|(s, _)| s.len() > depth)
+            .copied()
+            .collect();
+        for group in callees.chunk_by(|(a, _), (b, _)| a[depth] == b[depth]) {
+            let samples: u64 = group.iter().map(|(_, c)| c).sum();
+            let width = samples as f64 * self.scale;
+            // anything thinner cannot be seen, and neither can what it called.
+            if width >= 0.1 {
+                self.draw(&group[0].0[depth], samples, depth + 1, x, res);
+                self.draw_callees(group, depth + 1, x, res);
+            }
+            x += width;
+        }
+    }
+
+    fn draw(&self, name: &str, samples: u64, level: usize, x: f64, res: &mut String) {
+        let width = samples as f64 * self.scale;
+        let y = self.bottom - (level + 1) as f64 * FRAME_HEIGHT;
+        let share = 100.0 * samples as f64 / self.total as f64;
+        let _ = writeln!(
+            res,
+            "  <g><title>{} ({samples} samples, {share:.1}%)</title><rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{width:.1}\" height=\"{:.1}\" fill=\"{}\" rx=\"2\"/>{}</g>",
+            escape(name),
+            FRAME_HEIGHT - 1.0,
+            colour(name),
+            label(name, width)
+                .map(|l| format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+                    x + 3.0,
+                    y + FRAME_HEIGHT - 4.5,
+                    escape(&l)
+                ))
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// As much of `name` as fits into a frame `width` wide, if enough of it does to be useful.
+fn label(name: &str, width: f64) -> Option<String> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let fits = ((width - 6.0) / CHAR_WIDTH).max(0.0) as usize;
+    let len = name.chars().count();
+    if len <= fits {
+        Some(name.to_string())
+    } else if fits >= 3 {
+        Some(format!(
+            "{}..",
+            name.chars().take(fits - 2).collect::<String>()
+        ))
+    } else {
+        None
+    }
+}
+
+/// A warm colour that is the same for every frame of a function, so it can be followed across
+/// the graph.
+fn colour(name: &str) -> String {
+    let hash = fnv1a(name.as_bytes());
+    let r = 205 + hash % 50;
+    let g = (hash >> 8) % 230;
+    let b = (hash >> 16) % 55;
+    format!("rgb({r},{g},{b})")
+}
+
+/// Runs `func` in a loop while sampling it, then writes the profile to `data/profiles`. Each round
+/// of the loop runs the part as often as `--time` benches it, see [`bench_iterations`], and rounds
+/// are repeated until there are enough samples for a useful graph.
+///
+/// [`bench_iterations`]: crate::template::runner::bench_iterations
+#[cfg(all(feature = "profile-cpu", unix))]
+pub(crate) fn run<I, T>(
+    func: Arc<dyn Fn(I) -> T + Send + Sync>,
+    input: I,
+    base_time: &Duration,
+    day: Day,
+    part: u8,
+) -> anyhow::Result<Summary>
+where
+    I: Clone + Send + 'static,
+    T: 'static,
+{
+    use anyhow::{anyhow, Context};
+    use std::time::Instant;
+    use std::{fs, thread};
+
+    use crate::template::runner::{bench_iterations, SOLUTION_STACK_SIZE};
+
+    /// Sampling is stopped after this long even if there are only few samples.
+    const MAX_PROFILE_TIME: Duration = Duration::from_secs(10);
+    const MIN_SAMPLES: usize = 1000;
+
+    let iterations = bench_iterations(base_time);
+
+    sampling::start().context("could not start the sampling timer")?;
+    let profiled = thread::Builder::new()
+        .name("profile".into())
+        .stack_size(SOLUTION_STACK_SIZE)
+        .spawn(move || {
+            sampling::sample_this_thread();
+            let start = Instant::now();
+            let mut runs = 0;
+            sample_loop(&mut || {
+                while runs == 0
+                    || (sampling::samples() < MIN_SAMPLES && start.elapsed() < MAX_PROFILE_TIME)
+                {
+                    for _ in 0..iterations {
+                        func(input.clone());
+                    }
+                    runs += iterations;
+                }
+            });
+            runs
+        });
+    let runs = profiled.map(thread::JoinHandle::join);
+    let stacks = sampling::stop();
+    let runs = runs
+        .context("could not spawn the profiling thread")?
+        .map_err(|_| anyhow!("the part panicked while it was profiled"))?;
+
+    let folded = fold(sampling::symbolize(&stacks));
+    let dir = get_profile_dir();
+    fs::create_dir_all(&dir).with_context(|| format!("could not create {}", dir.display()))?;
+    let name = format!("{day}-p{part}");
+    let svg = dir.join(format!("{name}.svg"));
+    let folded_path = dir.join(format!("{name}.folded"));
+    let title = format!(
+        "Day {day} part {part}: {} samples over {runs} runs",
+        stacks.len()
+    );
+    fs::write(&svg, to_svg(&folded, &title))
+        .with_context(|| format!("could not write {}", svg.display()))?;
+    fs::write(&folded_path, to_folded(&folded))
+        .with_context(|| format!("could not write {}", folded_path.display()))?;
+
+    Ok(Summary {
+        runs,
+        samples: stacks.len() as u64,
+        svg,
+        folded: folded_path,
+    })
+}
+
+#[cfg(not(all(feature = "profile-cpu", unix)))]
+pub(crate) fn run<I, T>(
+    _func: Arc<dyn Fn(I) -> T + Send + Sync>,
+    _input: I,
+    _base_time: &Duration,
+    day: Day,
+    _part: u8,
+) -> anyhow::Result<Summary> {
+    if cfg!(unix) {
+        anyhow::bail!("this build cannot sample, try `cargo solve {day} --profile-cpu`")
+    }
+    anyhow::bail!("sampling needs a Unix system")
+}
+
+/// Everything the profile shows happens below this frame, which marks where the part starts.
+#[cfg(all(feature = "profile-cpu", unix))]
+#[inline(never)]
+fn sample_loop(f: &mut dyn FnMut()) {
+    f();
+    // keeps this frame on the stack rather than turning the call into a jump.
+    std::hint::black_box(());
+}
+
+#[cfg(all(feature = "profile-cpu", unix))]
+mod sampling {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use std::{mem, ptr};
+
+    use hashbrown::HashMap;
+
+    /// Enough for the loop's 1000 samples and the rounds that overshoot them, about 4MB in all.
+    const MAX_SAMPLES: usize = 4096;
+    const MAX_DEPTH: usize = 128;
+    /// The kernel rounds this up to its tick, which is usually 1 to 10ms.
+    const INTERVAL: Duration = Duration::from_millis(1);
+
+    // the signal handler cannot allocate, so every sample has its place up front.
+    static FRAMES: [[AtomicUsize; MAX_DEPTH]; MAX_SAMPLES] =
+        [const { [const { AtomicUsize::new(0) }; MAX_DEPTH] }; MAX_SAMPLES];
+    static DEPTHS: [AtomicUsize; MAX_SAMPLES] = [const { AtomicUsize::new(0) }; MAX_SAMPLES];
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    /// The `pthread_t` of the thread that is sampled.
+    static PROFILED: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn on_sigprof(_signal: libc::c_int) {
+        // `ITIMER_PROF` signals whichever thread is busy. Threads spawned since `start` have the
+        // signal blocked, this turns away the ones that already ran, like `rayon`'s workers.
+        // SAFETY: `pthread_self` only reads the thread pointer.
+        if unsafe { libc::pthread_self() } as usize != PROFILED.load(Ordering::Relaxed) {
+            return;
+        }
+        let slot = NEXT.fetch_add(1, Ordering::Relaxed);
+        let Some(frames) = FRAMES.get(slot) else {
+            return;
+        };
+        let mut depth = 0;
+        // SAFETY: only the profiled thread gets here, and it is never interrupted by itself, so no
+        // two walks race on `backtrace`'s state and the frames are only written to by this walk.
+        // The walk is not async-signal-safe in general though: libgcc's unwinder finds the unwind
+        // tables through `dl_iterate_phdr`, which takes the dynamic loader's lock, and allocates
+        // for frames registered at run time. A sample taken while the part itself is loading a
+        // library or unwinding a panic could therefore deadlock. Solutions do neither in a loop,
+        // which is the same bet other in-process profilers make.
+        unsafe {
+            backtrace::trace_unsynchronized(|frame| {
+                frames[depth].store(frame.ip() as usize, Ordering::Relaxed);
+                depth += 1;
+                depth < MAX_DEPTH
+            });
+        }
+        // a sample only counts once its frames are stored.
+        DEPTHS[slot].store(depth, Ordering::Release);
+    }
+
+    /// Installs the handler and starts the timer. `SIGPROF` is blocked on the calling thread and so
+    /// on every thread spawned from it, until [`stop`].
+    pub(super) fn start() -> io::Result<()> {
+        for depth in &DEPTHS {
+            depth.store(0, Ordering::Relaxed);
+        }
+        NEXT.store(0, Ordering::Relaxed);
+        PROFILED.store(0, Ordering::Relaxed);
+        mask_sigprof(libc::SIG_BLOCK)?;
+
+        // SAFETY: a zeroed `sigaction` is valid, and the handler has the signature it expects.
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = on_sigprof as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            if libc::sigaction(libc::SIGPROF, &action, ptr::null_mut()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        set_timer(INTERVAL)
+    }
+
+    /// Makes the calling thread the one that is sampled.
+    pub(super) fn sample_this_thread() {
+        // SAFETY: `pthread_self` has no preconditions.
+        PROFILED.store(unsafe { libc::pthread_self() } as usize, Ordering::Relaxed);
+        // unblocking a signal cannot fail for a valid signal number.
+        let _ = mask_sigprof(libc::SIG_UNBLOCK);
+    }
+
+    fn mask_sigprof(how: libc::c_int) -> io::Result<()> {
+        // SAFETY: `set` is initialised by `sigemptyset` before it is used.
+        let res = unsafe {
+            let mut set: libc::sigset_t = mem::zeroed();
+            libc::sigemptyset(&mut set);
+            libc::sigaddset(&mut set, libc::SIGPROF);
+            libc::pthread_sigmask(how, &set, ptr::null_mut())
+        };
+        if res != 0 {
+            return Err(io::Error::from_raw_os_error(res));
+        }
+        Ok(())
+    }
+
+    /// How many samples have been taken since [`start`].
+    pub(super) fn samples() -> usize {
+        NEXT.load(Ordering::Relaxed).min(MAX_SAMPLES)
+    }
+
+    /// Stops sampling and returns the sampled stacks as instruction pointers, innermost first.
+    pub(super) fn stop() -> Vec<Vec<usize>> {
+        let _ = set_timer(Duration::ZERO);
+        // SAFETY: ignoring a signal is always valid. A `SIGPROF` still on its way is then dropped
+        // instead of ending the process.
+        unsafe {
+            libc::signal(libc::SIGPROF, libc::SIG_IGN);
+        }
+        let _ = mask_sigprof(libc::SIG_UNBLOCK);
+
+        (0..samples())
+            .filter_map(|slot| {
+                let depth = DEPTHS[slot].load(Ordering::Acquire);
+                (depth > 0).then(|| {
+                    FRAMES[slot][..depth]
+                        .iter()
+                        .map(|ip| ip.load(Ordering::Relaxed))
+                        .collect()
+                })
+            })
+            .collect()
+    }
+
+    // not bound by the `libc` crate on every platform, but part of POSIX.
+    extern "C" {
+        fn setitimer(
+            which: libc::c_int,
+            new_value: *const libc::itimerval,
+            old_value: *mut libc::itimerval,
+        ) -> libc::c_int;
+    }
+
+    fn set_timer(interval: Duration) -> io::Result<()> {
+        let interval = libc::timeval {
+            tv_sec: interval.as_secs() as libc::time_t,
+            tv_usec: libc::suseconds_t::from(interval.subsec_micros()),
+        };
+        let timer = libc::itimerval {
+            it_interval: interval,
+            it_value: interval,
+        };
+        // SAFETY: `timer` is a valid `itimerval` and the old value is not asked for.
+        if unsafe { setitimer(libc::ITIMER_PROF, &timer, ptr::null_mut()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Names the frames of every stack, outermost first. Stacks are cut down to what happened in
+    /// the part: the profiler's own frames are dropped, and so is everything that led up to the
+    /// loop when the sample was taken on the profiling thread.
+    pub(super) fn symbolize(stacks: &[Vec<usize>]) -> Vec<Vec<String>> {
+        let mut names: HashMap<usize, Vec<String>> = HashMap::new();
+        stacks
+            .iter()
+            .map(|ips| {
+                let mut stack: Vec<String> = ips
+                    .iter()
+                    .flat_map(|ip| names.entry(*ip).or_insert_with(|| resolve(*ip)).clone())
+                    .collect();
+                stack.reverse();
+
+                if let Some(ix) = stack.iter().position(|f| f.ends_with("on_sigprof")) {
+                    stack.truncate(ix);
+                    // the signal trampoline sits between the handler and the interrupted code.
+                    while stack
+                        .last()
+                        .is_some_and(|f| f.contains("restore_rt") || f.starts_with("0x"))
+                    {
+                        stack.pop();
+                    }
+                }
+                if let Some(ix) = stack.iter().rposition(|f| f.ends_with("sample_loop")) {
+                    // so are the runner's closures around the part, the graph starts at the part.
+                    let runner = stack[ix + 1..]
+                        .iter()
+                        .take_while(|f| {
+                            f.starts_with("advent_of_code::template::")
+                                || f.starts_with("core::ops::function::")
+                        })
+                        .count();
+                    stack.drain(..=ix + runner);
+                }
+                stack
+            })
+            .collect()
+    }
+
+    /// The function at `ip` and the functions inlined into it there, innermost first.
+    fn resolve(ip: usize) -> Vec<String> {
+        let mut names = vec![];
+        // a return address points just past the call, which may already be the next function.
+        backtrace::resolve(ip.saturating_sub(1) as *mut std::ffi::c_void, |symbol| {
+            if let Some(name) = symbol.name() {
+                names.push(format!("{name:#}"));
+            }
+        });
+        if names.is_empty() {
+            names.push(format!("{ip:#x}"));
+        }
+        names
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{fold, label, to_folded, to_svg};
+
+    fn stack(frames: &[&str]) -> Vec<String> {
+        frames.iter().map(|f| (*f).to_string()).collect()
+    }
+
+    #[test]
+    fn folds_stacks() {
+        let folded = fold([
+            stack(&["part_one", "parse"]),
+            stack(&["part_one", "search", "neighbours"]),
+            stack(&["part_one", "parse"]),
+            stack(&["part_one", "<[u8; 4] as Debug>::fmt"]),
+        ]);
+        assert_eq!(
+            to_folded(&folded),
+            "part_one;<[u8, 4] as Debug>::fmt 1\n\
+            part_one;parse 2\n\
+            part_one;search;neighbours 1\n"
+        );
+    }
+
+    #[test]
+    fn draws_frames_by_share() {
+        let folded = fold([
+            stack(&["part_one", "parse"]),
+            stack(&["part_one", "search"]),
+            stack(&["part_one", "search"]),
+            stack(&["part_one"]),
+        ]);
+        let svg = to_svg(&folded, "Day 03 part 1 <4 samples>");
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1200\" height=\"88\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">Day 03 part 1 &lt;4 samples&gt;</text>"));
+        assert_eq!(svg.matches("<rect").count(), 4);
+        assert!(svg.contains(
+            "<title>all (4 samples, 100.0%)</title><rect x=\"0.0\" y=\"62.0\" width=\"1200.0\""
+        ));
+        assert!(svg.contains("<title>part_one (4 samples, 100.0%)</title><rect x=\"0.0\" y=\"46.0\" width=\"1200.0\""));
+        // a quarter of the time is spent in `part_one` itself, so its callees start after it.
+        assert!(svg.contains(
+            "<title>parse (1 samples, 25.0%)</title><rect x=\"300.0\" y=\"30.0\" width=\"300.0\""
+        ));
+        assert!(svg.contains(
+            "<title>search (2 samples, 50.0%)</title><rect x=\"600.0\" y=\"30.0\" width=\"600.0\""
+        ));
+    }
+
+    #[test]
+    fn draws_empty_profiles() {
+        let svg = to_svg(&fold([]), "nothing");
+        assert_eq!(svg.matches("<rect").count(), 0);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn shortens_labels() {
+        assert_eq!(label("parse", 100.0), Some("parse".into()));
+        assert_eq!(label("neighbours", 48.0), Some("neig..".into()));
+        assert_eq!(label("neighbours", 20.0), None);
+    }
+}
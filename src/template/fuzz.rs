@@ -0,0 +1,352 @@
+/// Feeds mutated inputs to a day's parts to find inputs that make them panic or hang.
+///
+/// Solutions are expected to return an error for input they can not handle, never to panic. The
+/// fuzzer starts from the day's examples, mutates them at random and runs both parts on every
+/// mutation. Inputs that crash a part are shrunk to a small reproduction and stored under
+/// `data/fuzz/<day>/`, where the day's `fuzz_regressions` test replays them from then on.
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fs, panic, process};
+
+use crate::property::{fnv1a, Rng, Shrink};
+use crate::template::runner::{arg_value, get_timeout, run_isolated, Outcome, Variant};
+use crate::template::{read_examples, ANSI_BOLD, ANSI_RESET};
+use crate::Day;
+
+/// Parts that run longer than this on a fuzzed input are reported as hanging.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_RUNS: u32 = 2000;
+/// Mutations never grow an input past this size.
+const MAX_INPUT_LEN: usize = 16 * 1024;
+const MAX_SHRINKS: u32 = 2000;
+
+/// Bytes that carry meaning in puzzle inputs, so mutations that use them reach deeper into parsers.
+const INTERESTING_BYTES: &[u8] = b"0123456789 \n:,;.-#|=()LRSJF7AZ";
+const INTERESTING_NUMBERS: &[&str] = &[
+    "0",
+    "1",
+    "-1",
+    "2147483648",
+    "4294967296",
+    "9223372036854775807",
+    "18446744073709551616",
+    "340282366920938463463374607431768211456",
+];
+
+/// Why an input is kept as a regression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrashKind {
+    Panic,
+    Hang,
+}
+
+impl CrashKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            CrashKind::Panic => "crash",
+            CrashKind::Hang => "hang",
+        }
+    }
+}
+
+/// A part that panicked or hung on an input.
+#[derive(Clone, Debug)]
+pub struct Crash {
+    pub part: u8,
+    pub kind: CrashKind,
+    pub message: String,
+    pub input: String,
+}
+
+#[must_use]
+pub fn get_regression_dir(day: Day) -> PathBuf {
+    Path::new("data").join("fuzz").join(day.to_string())
+}
+
+/// Runs a part on a thread of its own, so panics are caught and hangs are cut short.
+fn run_one(part: &Variant, input: &str, timeout: Duration) -> Option<Crash> {
+    let func = part.func;
+    let (outcome, _) = run_isolated(
+        Arc::new(move |input: String| func(&input)),
+        input.to_string(),
+        Some(timeout),
+    );
+    let (kind, message) = match outcome {
+        Outcome::Panicked(msg) => (CrashKind::Panic, msg),
+        Outcome::TimedOut(t) => (CrashKind::Hang, format!("did not finish within {t:.1?}")),
+        _ => return None,
+    };
+    Some(Crash {
+        part: part.part,
+        kind,
+        message,
+        input: input.to_string(),
+    })
+}
+
+fn run_all(parts: &[Variant], input: &str, timeout: Duration) -> Option<Crash> {
+    parts.iter().find_map(|part| run_one(part, input, timeout))
+}
+
+/// The day's examples, which the fuzzer mutates, plus every stored regression.
+fn seed_corpus(day: Day) -> Vec<String> {
+    let mut corpus = vec![String::new()];
+    corpus.extend(read_examples(day).into_iter().map(|(_, s)| s));
+    corpus.extend(read_regressions(day).into_iter().map(|(_, s)| s));
+    corpus
+}
+
+fn read_regressions(day: Day) -> Vec<(PathBuf, String)> {
+    let Ok(entries) = fs::read_dir(get_regression_dir(day)) else {
+        return vec![];
+    };
+    let mut files: Vec<(PathBuf, String)> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "txt"))
+        .filter_map(|p| {
+            let bytes = fs::read(&p).ok()?;
+            Some((p, String::from_utf8_lossy(&bytes).into_owned()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn mutate(rng: &mut Rng, corpus: &[String]) -> String {
+    let mut bytes = rng.pick(corpus).as_bytes().to_vec();
+    for _ in 0..=rng.below(4) {
+        let len = bytes.len();
+        let pos = if len == 0 { 0 } else { rng.below(len + 1) };
+        match rng.below(8) {
+            0 if len > 0 => bytes[pos.min(len - 1)] = *rng.pick(INTERESTING_BYTES),
+            1 => bytes.insert(pos, *rng.pick(INTERESTING_BYTES)),
+            2 => bytes.insert(pos, rng.below(128) as u8),
+            3 if len > 0 => {
+                let end = (pos + 1 + rng.below(16)).min(len);
+                bytes.drain(pos.min(len - 1)..end);
+            }
+            4 if len > 0 => {
+                let start = pos.min(len - 1);
+                let end = (start + 1 + rng.below(64)).min(len);
+                let copy = bytes[start..end].to_vec();
+                bytes.splice(start..start, copy);
+            }
+            5 => bytes.truncate(pos),
+            6 => {
+                let number = rng.pick(INTERESTING_NUMBERS).as_bytes();
+                // replaces the run of digits at `pos`, if there is one.
+                let end = bytes[pos..]
+                    .iter()
+                    .position(|b| !b.is_ascii_digit())
+                    .map_or(len, |n| pos + n);
+                bytes.splice(pos..end, number.iter().copied());
+            }
+            _ => {
+                let other = rng.pick(corpus).as_bytes();
+                let cut = if other.is_empty() {
+                    0
+                } else {
+                    rng.below(other.len())
+                };
+                bytes.truncate(pos);
+                bytes.extend_from_slice(&other[cut..]);
+            }
+        }
+        bytes.truncate(MAX_INPUT_LEN);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Shrinks a panicking input line by line and then character by character, keeping it panicking
+/// in the same part. Hangs are kept as they are, every shrinking step would take a full timeout.
+fn minimise(parts: &[Variant], crash: Crash, timeout: Duration) -> Crash {
+    if crash.kind == CrashKind::Hang {
+        return crash;
+    }
+    let part = parts.iter().find(|p| p.part == crash.part).unwrap();
+    let lines: Vec<String> = crash.input.split('\n').map(String::from).collect();
+    let mut best = (lines, crash);
+    let mut shrinks = 0;
+    'shrinking: loop {
+        for candidate in best.0.shrink() {
+            if shrinks == MAX_SHRINKS {
+                break 'shrinking;
+            }
+            shrinks += 1;
+            let input = candidate.join("\n");
+            if let Some(c) = run_one(part, &input, timeout) {
+                if c.kind == best.1.kind {
+                    best = (candidate, c);
+                    continue 'shrinking;
+                }
+            }
+        }
+        break;
+    }
+    best.1
+}
+
+fn save(day: Day, crash: &Crash) -> std::io::Result<PathBuf> {
+    let dir = get_regression_dir(day);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}-{:016x}.txt",
+        crash.kind.prefix(),
+        fnv1a(crash.input.as_bytes())
+    ));
+    fs::write(&path, &crash.input)?;
+    Ok(path)
+}
+
+/// Runs every stored regression of the day, returning a description of each one that still crashes.
+pub fn replay(day: Day, parts: &[Variant]) -> Vec<String> {
+    read_regressions(day)
+        .into_iter()
+        .filter_map(|(path, input)| {
+            // hangs are given a lot more time here, a slow debug build is not a hang.
+            let crash = run_all(parts, &input, DEFAULT_TIMEOUT * 10)?;
+            Some(format!(
+                "{}: part {} {}: {}",
+                path.display(),
+                crash.part,
+                match crash.kind {
+                    CrashKind::Panic => "panicked",
+                    CrashKind::Hang => "hung",
+                },
+                crash.message
+            ))
+        })
+        .collect()
+}
+
+/// `--fuzz` fuzzes the day's parts instead of running them on the input.
+pub fn is_fuzz() -> bool {
+    env::args().any(|x| x == "--fuzz")
+}
+
+/// Replays the day's regressions, then fuzzes its parts for `--runs` inputs (or until the first
+/// new crash) and stores what it finds. Exits with an error if any input crashes a part.
+pub fn run(day: Day, parts: &[Variant]) {
+    let runs = arg_value("--runs")
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(DEFAULT_RUNS);
+    let timeout = get_timeout().unwrap_or(DEFAULT_TIMEOUT);
+    let seed = arg_value("--seed")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64)
+        });
+
+    // the parts' panics are expected and reported below, the default hook would only add noise.
+    panic::set_hook(Box::new(|_| {}));
+
+    let failing = replay(day, parts);
+    for f in &failing {
+        println!("✖ regression still fails: {f}");
+    }
+
+    println!("{ANSI_BOLD}Fuzzing day {day}{ANSI_RESET} ({runs} runs, seed {seed})");
+    let corpus = seed_corpus(day);
+    let mut rng = Rng::new(seed);
+    let mut found = None;
+    for run in 0..runs {
+        if run % 100 == 0 {
+            print!("\r{run}/{runs}");
+            let _ = stdout().flush();
+        }
+        let input = mutate(&mut rng, &corpus);
+        if let Some(crash) = run_all(parts, &input, timeout) {
+            found = Some(crash);
+            break;
+        }
+    }
+    print!("\r");
+
+    let Some(crash) = found else {
+        println!("No crashes in {runs} runs.");
+        if !failing.is_empty() {
+            process::exit(1);
+        }
+        return;
+    };
+
+    let crash = minimise(parts, crash, timeout);
+    println!(
+        "✖ part {} {}: {}",
+        crash.part,
+        match crash.kind {
+            CrashKind::Panic => "panicked",
+            CrashKind::Hang => "hung",
+        },
+        crash.message
+    );
+    println!("minimal input:\n{}", crash.input);
+    match save(day, &crash) {
+        Ok(path) => println!("Saved as a regression to {}", path.display()),
+        Err(e) => eprintln!("Could not save the regression: {e}"),
+    }
+    process::exit(1);
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{minimise, mutate, run_all, CrashKind, Variant};
+    use crate::property::Rng;
+    use crate::template::runner::{outcome_of, Outcome};
+    use std::time::Duration;
+
+    fn parts() -> [Variant; 2] {
+        [
+            Variant {
+                part: 1,
+                name: "default",
+                func: |input| {
+                    outcome_of(
+                        input
+                            .lines()
+                            .map(|l| l.parse::<i64>())
+                            .sum::<Result<i64, _>>(),
+                    )
+                },
+            },
+            Variant {
+                part: 2,
+                name: "default",
+                func: |input| {
+                    // panics on any line with a `#`.
+                    let hashes = input.lines().filter(|l| l.contains('#')).count();
+                    assert_eq!(hashes, 0, "found a hash");
+                    Outcome::Solved(input.len().into())
+                },
+            },
+        ]
+    }
+
+    #[test]
+    fn mutations_stay_small_and_vary() {
+        let corpus = vec!["12 34\n56".to_string()];
+        let mut rng = Rng::new(5);
+        let mutations: Vec<String> = (0..200).map(|_| mutate(&mut rng, &corpus)).collect();
+        assert!(mutations.iter().all(|m| m.len() <= super::MAX_INPUT_LEN));
+        assert!(mutations.iter().any(|m| m != &corpus[0]));
+    }
+
+    #[test]
+    fn finds_and_minimises_crashes() {
+        let timeout = Duration::from_secs(5);
+        assert!(run_all(&parts(), "1\n2", timeout).is_none());
+        // part 1 returns an error, which is fine.
+        assert!(run_all(&parts(), "x", timeout).is_none());
+
+        let crash = run_all(&parts(), "1\n2\nab#cd\n3", timeout).unwrap();
+        assert_eq!((crash.part, crash.kind), (2, CrashKind::Panic));
+        let crash = minimise(&parts(), crash, timeout);
+        assert_eq!(crash.input, "#");
+        assert!(crash.message.contains("found a hash"));
+    }
+}
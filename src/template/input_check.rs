@@ -0,0 +1,233 @@
+/// Looks for the ways an input file tends to get mangled on its way to `data/inputs`.
+///
+/// An input saved with CRLF line endings or trailing whitespace, without its final newline, or
+/// simply the wrong file, usually still parses and just gives a wrong answer. The runner warns
+/// about these before solving, and `cargo check-input <day>` reports them on its own. A day can
+/// also check the shape of its input in more detail, see [`Schema`].
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+
+use crate::template::{read_examples, ANSI_BOLD, ANSI_RESET};
+use crate::Day;
+
+/// A day's check that its input has the expected shape, e.g. that every line is a hand and a bid.
+/// It should describe the first thing that does not fit rather than try to solve anything.
+pub type Schema = fn(&str) -> anyhow::Result<()>;
+
+/// Something about an input that is likely to make a solution go wrong.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    Empty,
+    CrLf {
+        lines: usize,
+    },
+    TrailingWhitespace {
+        first_line: usize,
+        lines: usize,
+    },
+    MissingFinalNewline,
+    NonAscii {
+        line: usize,
+        column: usize,
+        count: usize,
+    },
+    SameAsExample(PathBuf),
+    Schema(String),
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::Empty => write!(f, "the input is empty"),
+            Issue::CrLf { lines } => write!(f, "{lines} line(s) end in CRLF (`\\r\\n`)"),
+            Issue::TrailingWhitespace { first_line, lines } => write!(
+                f,
+                "{lines} line(s) end in whitespace, the first is line {first_line}"
+            ),
+            Issue::MissingFinalNewline => write!(f, "the last line does not end in a newline"),
+            Issue::NonAscii {
+                line,
+                column,
+                count,
+            } => write!(
+                f,
+                "{count} character(s) are not ASCII, the first is at line {line}, column {column}"
+            ),
+            Issue::SameAsExample(path) => {
+                write!(f, "the input is the same as {}", path.display())
+            }
+            Issue::Schema(e) => write!(f, "the input does not fit the day's schema: {e}"),
+        }
+    }
+}
+
+/// The day-independent checks: line endings, whitespace, encoding and whether `input` is really
+/// one of the `examples`. Lines and columns are one-based.
+#[must_use]
+pub fn lint(input: &str, examples: &[(PathBuf, String)]) -> Vec<Issue> {
+    if input.trim().is_empty() {
+        return vec![Issue::Empty];
+    }
+
+    let mut issues = vec![];
+    let lines: Vec<&str> = input.split_inclusive('\n').collect();
+
+    let crlf = lines.iter().filter(|l| l.ends_with("\r\n")).count();
+    if crlf > 0 {
+        issues.push(Issue::CrLf { lines: crlf });
+    }
+
+    let trailing: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| {
+            let l = l.trim_end_matches(['\n', '\r']);
+            l.ends_with([' ', '\t'])
+        })
+        .map(|(ix, _)| ix + 1)
+        .collect();
+    if let Some(first_line) = trailing.first() {
+        issues.push(Issue::TrailingWhitespace {
+            first_line: *first_line,
+            lines: trailing.len(),
+        });
+    }
+
+    if !input.ends_with('\n') {
+        issues.push(Issue::MissingFinalNewline);
+    }
+
+    let non_ascii = lines.iter().enumerate().flat_map(|(ix, l)| {
+        l.chars()
+            .enumerate()
+            .filter(|(_, c)| !c.is_ascii())
+            .map(move |(col, _)| (ix + 1, col + 1))
+    });
+    let (count, first) = non_ascii.fold((0, None), |(n, first), pos| (n + 1, first.or(Some(pos))));
+    if let Some((line, column)) = first {
+        issues.push(Issue::NonAscii {
+            line,
+            column,
+            count,
+        });
+    }
+
+    if let Some((path, _)) = examples
+        .iter()
+        .find(|(_, example)| example.trim_end() == input.trim_end())
+    {
+        issues.push(Issue::SameAsExample(path.clone()));
+    }
+
+    issues
+}
+
+/// [`lint`]s `input` against the day's examples and runs its schema, if it has one.
+#[must_use]
+pub fn check(input: &str, day: Day, schema: Option<Schema>) -> Vec<Issue> {
+    let mut issues = lint(input, &read_examples(day));
+    // a schema has nothing to say about an empty input.
+    if issues != [Issue::Empty] {
+        if let Some(Err(e)) = schema.map(|schema| schema(input)) {
+            issues.push(Issue::Schema(format!("{e:#}")));
+        }
+    }
+    issues
+}
+
+/// Prints a warning for every issue with the input, before the runner solves it.
+pub fn warn(input: &str, day: Day, schema: Option<Schema>) {
+    for issue in check(input, day, schema) {
+        eprintln!("Warning: {issue}. Try `cargo check-input {day}`.");
+    }
+}
+
+/// `--check-input` checks the day's input instead of running its parts.
+pub fn is_check_input() -> bool {
+    env::args().any(|x| x == "--check-input")
+}
+
+/// Reports every issue with `data/inputs/<day>.txt`, exiting with an error if there are any.
+pub fn run(day: Day, schema: Option<Schema>) {
+    let path = Path::new("data").join("inputs").join(format!("{day}.txt"));
+    // read as bytes, so an input that is not valid UTF-8 is reported rather than a crash.
+    let input = match fs::read(&path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => {
+            eprintln!("Could not read {}: {e}", path.display());
+            process::exit(1);
+        }
+    };
+
+    let issues = check(&input, day, schema);
+    println!("{ANSI_BOLD}{}{ANSI_RESET}", path.display());
+    if issues.is_empty() {
+        let lines = input.lines().count();
+        println!("✔ {lines} lines, no issues found.");
+        return;
+    }
+    for issue in &issues {
+        println!("✖ {issue}");
+    }
+    process::exit(1);
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{lint, Issue};
+    use std::path::PathBuf;
+
+    fn examples() -> Vec<(PathBuf, String)> {
+        vec![(PathBuf::from("data/examples/01.txt"), "1abc2\nx3y\n".into())]
+    }
+
+    #[test]
+    fn accepts_clean_input() {
+        assert_eq!(lint("12\n34\n", &examples()), vec![]);
+    }
+
+    #[test]
+    fn reports_empty_input() {
+        assert_eq!(lint("", &examples()), vec![Issue::Empty]);
+        assert_eq!(lint(" \n\n", &examples()), vec![Issue::Empty]);
+    }
+
+    #[test]
+    fn reports_formatting() {
+        assert_eq!(
+            lint("12\r\n34 \r\n56\t\n78", &examples()),
+            vec![
+                Issue::CrLf { lines: 2 },
+                Issue::TrailingWhitespace {
+                    first_line: 2,
+                    lines: 2
+                },
+                Issue::MissingFinalNewline,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_non_ascii() {
+        assert_eq!(
+            lint("12\n3–4\n5–\n", &examples()),
+            vec![Issue::NonAscii {
+                line: 2,
+                column: 2,
+                count: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_examples() {
+        assert_eq!(
+            lint("1abc2\nx3y", &examples()),
+            vec![
+                Issue::MissingFinalNewline,
+                Issue::SameAsExample(PathBuf::from("data/examples/01.txt"))
+            ]
+        );
+    }
+}
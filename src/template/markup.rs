@@ -0,0 +1,9 @@
+//! Helpers shared by the generated SVG and HTML files (benchmark reports, flamegraphs).
+
+/// Escapes `s` for use as text or as a quoted attribute value in SVG and HTML.
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
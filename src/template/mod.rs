@@ -1,9 +1,18 @@
 use crate::Day;
+use std::path::{Path, PathBuf};
 use std::{env, fs};
 
+pub mod answer;
 pub mod aoc_cli;
+pub mod benchmark_report;
 pub mod commands;
+pub mod cpu_profile;
+pub mod fuzz;
+pub mod input_check;
+mod markup;
+pub mod readme;
 pub mod readme_benchmarks;
+pub mod readme_stars;
 pub mod runner;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
@@ -29,10 +38,42 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
     f.expect("could not open input file")
 }
 
+/// Every example file of the day (`DD.txt` and the per-part `DD-N.txt`) that exists, with its contents.
+#[must_use]
+pub fn read_examples(day: Day) -> Vec<(PathBuf, String)> {
+    let dir = Path::new("data").join("examples");
+    std::iter::once(format!("{day}.txt"))
+        .chain((1..=9).map(|n| format!("{day}-{n}.txt")))
+        .map(|name| dir.join(name))
+        .filter_map(|path| fs::read_to_string(&path).ok().map(|s| (path, s)))
+        .collect()
+}
+
 /// Creates the constant `DAY` and sets up the input and runner for each part.
+///
+/// A day can also name a function that explains its answers (see [`runner::Explain`]), which is
+/// run instead of the parts by `cargo solve <day> --explain`:
+/// `advent_of_code::solution!(7, explain = explain);`
+///
+/// Alternative implementations of a part are listed as `(part, name, function)`. They are run
+/// with `cargo solve <day> --variant <name>` and checked against each other with `--compare`:
+/// `advent_of_code::solution!(5, variants = [(2, "brute", part_two_brute)]);`
+///
+/// A function that checks the shape of the input (see [`input_check::Schema`]) is run before
+/// solving and by `cargo check-input <day>`: `advent_of_code::solution!(1, schema = schema);`
+/// The options can be combined, in the order `explain`, `schema`, `variants`.
+///
+/// `cargo solve <day> --fuzz` feeds mutated inputs to both parts instead (see [`fuzz`]), and the
+/// generated `fuzz_regressions` test makes sure none of the stored crashing inputs panic anymore.
 #[macro_export]
 macro_rules! solution {
-    ($day:expr) => {
+    (@some) => {
+        None
+    };
+    (@some $x:path) => {
+        Some($x)
+    };
+    (@main $day:expr, $explain:expr, $schema:expr, [$(($part:expr, $name:expr, $func:path)),*]) => {
         /// The current day.
         const DAY: advent_of_code::Day = advent_of_code::day!($day);
 
@@ -40,11 +81,73 @@ macro_rules! solution {
         #[global_allocator]
         static ALLOC: dhat::Alloc = dhat::Alloc;
 
+        /// Both parts as they run by default.
+        fn default_parts() -> [advent_of_code::template::runner::Variant; 2] {
+            use advent_of_code::template::runner::{outcome_of, Variant};
+            [
+                Variant { part: 1, name: "default", func: |input| outcome_of(part_one(input)) },
+                Variant { part: 2, name: "default", func: |input| outcome_of(part_two(input)) },
+            ]
+        }
+
+        #[cfg(test)]
+        mod fuzz_regressions {
+            #[test]
+            fn stored_crashes_do_not_panic() {
+                let failing = advent_of_code::template::fuzz::replay(super::DAY, &super::default_parts());
+                assert!(failing.is_empty(), "{}", failing.join("\n"));
+            }
+        }
+
         fn main() {
             use advent_of_code::template::runner::*;
-            let input = advent_of_code::template::read_file("inputs", DAY);
-            run_part(part_one, &input, DAY, 1);
-            run_part(part_two, &input, DAY, 2);
+            if advent_of_code::template::fuzz::is_fuzz() {
+                advent_of_code::template::fuzz::run(DAY, &default_parts());
+                return;
+            }
+            if advent_of_code::template::input_check::is_check_input() {
+                advent_of_code::template::input_check::run(DAY, $schema);
+                return;
+            }
+            // parts run on their own threads, so the input has to outlive `main`.
+            let input: &'static str =
+                Box::leak(advent_of_code::template::read_file("inputs", DAY).into_boxed_str());
+            advent_of_code::template::input_check::warn(input, DAY, $schema);
+            if is_explain() {
+                run_explain($explain, input, DAY);
+                return;
+            }
+            let variants: &[Variant] = &[$(Variant {
+                part: $part,
+                name: $name,
+                func: |input| outcome_of($func(input)),
+            }),*];
+            if is_compare() {
+                run_compare(default_parts(), variants, input, DAY);
+                return;
+            }
+            match selected_variant(variants, 1, DAY) {
+                Some(variant) => run_variant(variant, input, DAY),
+                None => run_part(part_one, input, DAY, 1),
+            }
+            match selected_variant(variants, 2, DAY) {
+                Some(variant) => run_variant(variant, input, DAY),
+                None => run_part(part_two, input, DAY, 2),
+            }
         }
     };
+    (
+        $day:expr
+        $(, explain = $explain:path)?
+        $(, schema = $schema:path)?
+        $(, variants = [$(($part:expr, $name:expr, $func:path)),* $(,)?])?
+        $(,)?
+    ) => {
+        advent_of_code::solution!(
+            @main $day,
+            advent_of_code::solution!(@some $($explain)?),
+            advent_of_code::solution!(@some $($schema)?),
+            [$($(($part, $name, $func)),*)?]
+        );
+    };
 }
@@ -0,0 +1,396 @@
+/// Module that replaces marker-delimited sections of the readme.
+/// Each section sits between two `<!--- name --->` markers and is owned by whatever generates it
+/// (benchmarks, stars, ...), so several of them can be kept up to date independently.
+use std::cmp::Ordering;
+use std::path::Path;
+use std::{fs, io};
+
+#[derive(Debug)]
+pub enum Error {
+    Parser(String),
+    IO(io::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IO(e)
+    }
+}
+
+/// The comment that opens and closes the section called `name`.
+#[must_use]
+pub fn marker(name: &str) -> String {
+    format!("<!--- {name} --->")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    fn separator(self) -> &'static str {
+        match self {
+            Align::Left => ":---",
+            Align::Center => ":---:",
+            Align::Right => "---:",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Column {
+    pub header: String,
+    pub align: Align,
+}
+
+impl Column {
+    pub fn new(header: impl Into<String>, align: Align) -> Self {
+        Column {
+            header: header.into(),
+            align,
+        }
+    }
+}
+
+/// A table cell. Cells with a `key` sort by it, the others sort by their text after them.
+#[derive(Clone, Debug)]
+pub struct Cell {
+    pub text: String,
+    pub key: Option<f64>,
+}
+
+impl Cell {
+    pub fn text(text: impl Into<String>) -> Self {
+        Cell {
+            text: text.into(),
+            key: None,
+        }
+    }
+
+    pub fn keyed(text: impl Into<String>, key: f64) -> Self {
+        Cell {
+            text: text.into(),
+            key: Some(key),
+        }
+    }
+
+    /// Orders cells within their group, keyed cells always come before the others.
+    fn compare(&self, other: &Self, descending: bool) -> Ordering {
+        let within = |ord: Ordering| if descending { ord.reverse() } else { ord };
+        match (self.key, other.key) {
+            (Some(a), Some(b)) => within(a.total_cmp(&b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => within(self.text.cmp(&other.text)),
+        }
+    }
+}
+
+/// Which column the rows of a table are ordered by. Rows that compare equal keep their order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// The order the rows were added in.
+    #[default]
+    AsGiven,
+    Ascending(usize),
+    Descending(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+    sort: SortOrder,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>, sort: SortOrder) -> Self {
+        Table {
+            columns,
+            rows: vec![],
+            sort,
+        }
+    }
+
+    /// Adds a row, which has to have a cell for every column.
+    pub fn push(&mut self, row: Vec<Cell>) -> Result<(), Error> {
+        if row.len() != self.columns.len() {
+            return Err(Error::Parser(format!(
+                "a row has {} cells, but the table has {} columns",
+                row.len(),
+                self.columns.len()
+            )));
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    fn sorted_rows(&self) -> Vec<&Vec<Cell>> {
+        let mut rows: Vec<&Vec<Cell>> = self.rows.iter().collect();
+        match self.sort {
+            SortOrder::AsGiven => {}
+            SortOrder::Ascending(col) if col < self.columns.len() => {
+                rows.sort_by(|a, b| a[col].compare(&b[col], false));
+            }
+            SortOrder::Descending(col) if col < self.columns.len() => {
+                rows.sort_by(|a, b| a[col].compare(&b[col], true));
+            }
+            // sorting by a column that is not there leaves the rows as they are.
+            SortOrder::Ascending(_) | SortOrder::Descending(_) => {}
+        }
+        rows
+    }
+
+    fn render(&self) -> Vec<String> {
+        let line = |cells: Vec<&str>| format!("| {} |", cells.join(" | "));
+        let mut lines = vec![
+            line(self.columns.iter().map(|c| c.header.as_str()).collect()),
+            line(self.columns.iter().map(|c| c.align.separator()).collect()),
+        ];
+        for row in self.sorted_rows() {
+            lines.push(line(row.iter().map(|c| c.text.as_str()).collect()));
+        }
+        lines
+    }
+}
+
+/// The generated content of one readme section: a heading, an optional table and any lines below.
+#[derive(Clone, Debug)]
+pub struct Section {
+    pub name: String,
+    pub heading: String,
+    pub table: Option<Table>,
+    pub footer: Vec<String>,
+}
+
+impl Section {
+    pub fn new(name: impl Into<String>, heading: impl Into<String>) -> Self {
+        Section {
+            name: name.into(),
+            heading: heading.into(),
+            table: None,
+            footer: vec![],
+        }
+    }
+
+    /// The section as it appears in the readme, markers included.
+    fn render(&self) -> String {
+        let marker = marker(&self.name);
+        let mut lines = vec![marker.clone(), self.heading.clone(), String::new()];
+        if let Some(table) = &self.table {
+            lines.extend(table.render());
+        }
+        if !self.footer.is_empty() {
+            lines.push(String::new());
+            lines.extend(self.footer.iter().cloned());
+        }
+        lines.push(marker);
+        lines.join("\n")
+    }
+}
+
+/// Byte ranges of every occurrence of the section called `name`, markers included.
+/// Markers pair up in order, so a section can be shown in more than one place.
+fn locate_sections(readme: &str, name: &str) -> Result<Vec<(usize, usize)>, Error> {
+    let marker = marker(name);
+    let matches: Vec<usize> = readme.match_indices(&marker).map(|m| m.0).collect();
+
+    if matches.is_empty() {
+        return Err(Error::Parser(format!(
+            "could not find the `{marker}` section in the readme"
+        )));
+    }
+
+    if !matches.len().is_multiple_of(2) {
+        return Err(Error::Parser(format!(
+            "`{marker}` appears {} times in the readme, expected an opening and a closing marker \
+            for every section",
+            matches.len()
+        )));
+    }
+
+    Ok(matches
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1] + marker.len()))
+        .collect())
+}
+
+/// Replaces every occurrence of `section` in `readme`.
+pub fn replace_section(readme: &mut String, section: &Section) -> Result<(), Error> {
+    let positions = locate_sections(readme, &section.name)?;
+    let rendered = section.render();
+    // back to front, so the earlier positions stay valid.
+    for (start, end) in positions.into_iter().rev() {
+        readme.replace_range(start..end, &rendered);
+    }
+    Ok(())
+}
+
+/// Writes `contents` next to `path` first and then moves it into place, so `path` is never left
+/// half-written.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+    let tmp = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let written = fs::File::create(&tmp).and_then(|mut f| {
+        io::Write::write_all(&mut f, contents.as_bytes())?;
+        f.sync_all()
+    });
+    let res = written.and_then(|()| fs::rename(&tmp, path));
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    res
+}
+
+/// Replaces `sections` in the readme at `path`. Nothing is written unless every section is found.
+pub fn update_file(path: &Path, sections: &[Section]) -> Result<(), Error> {
+    let mut readme = fs::read_to_string(path)?;
+    for section in sections {
+        replace_section(&mut readme, section)?;
+    }
+    write_atomic(path, &readme)?;
+    Ok(())
+}
+
+/// Replaces `sections` in the project's `README.md`.
+pub fn update(sections: &[Section]) -> Result<(), Error> {
+    update_file(Path::new("README.md"), sections)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{
+        marker, replace_section, update_file, Align, Cell, Column, Section, SortOrder, Table,
+    };
+    use std::{env, fs, process};
+
+    fn table(sort: SortOrder) -> Table {
+        let mut table = Table::new(
+            vec![
+                Column::new("Name", Align::Left),
+                Column::new("Time", Align::Right),
+            ],
+            sort,
+        );
+        for (name, time, key) in [
+            ("b", "2ms", Some(2e6)),
+            ("a", "-", None),
+            ("c", "1s", Some(1e9)),
+        ] {
+            let time = key.map_or_else(|| Cell::text(time), |k| Cell::keyed(time, k));
+            table.push(vec![Cell::text(name), time]).unwrap();
+        }
+        table
+    }
+
+    fn section(name: &str, sort: SortOrder) -> Section {
+        Section {
+            table: Some(table(sort)),
+            footer: vec!["done".into()],
+            ..Section::new(name, "## Things")
+        }
+    }
+
+    #[test]
+    fn renders_sections() {
+        let mut s = format!("foo\n{0}{0}\nbar", marker("things"));
+        replace_section(&mut s, &section("things", SortOrder::AsGiven)).unwrap();
+        let expected = [
+            "foo",
+            "<!--- things --->",
+            "## Things",
+            "",
+            "| Name | Time |",
+            "| :--- | ---: |",
+            "| b | 2ms |",
+            "| a | - |",
+            "| c | 1s |",
+            "",
+            "done",
+            "<!--- things --->",
+            "bar",
+        ]
+        .join("\n");
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn sorts_rows() {
+        let names = |sort| {
+            table(sort)
+                .sorted_rows()
+                .iter()
+                .map(|r| r[0].text.clone())
+                .collect::<String>()
+        };
+        assert_eq!(names(SortOrder::Ascending(0)), "abc");
+        assert_eq!(names(SortOrder::Descending(0)), "cba");
+        // keyed cells compare by their key, so `1s` is slower than `2ms`.
+        assert_eq!(names(SortOrder::Ascending(1)), "bca");
+        // cells without a key stay last either way.
+        assert_eq!(names(SortOrder::Descending(1)), "cba");
+        assert_eq!(names(SortOrder::Ascending(7)), "bac");
+    }
+
+    #[test]
+    fn rejects_rows_that_do_not_fit() {
+        let mut t = table(SortOrder::AsGiven);
+        assert!(t.push(vec![Cell::text("x")]).is_err());
+    }
+
+    #[test]
+    fn updates_sections_independently() {
+        let (one, two) = (marker("one"), marker("two"));
+        let mut s = format!("{one}{one}\n{two}old{two}\n{one}{one}");
+        replace_section(&mut s, &section("one", SortOrder::AsGiven)).unwrap();
+        replace_section(&mut s, &section("one", SortOrder::AsGiven)).unwrap();
+        assert_eq!(s.matches(&one).count(), 4);
+        assert_eq!(s.matches("## Things").count(), 2);
+        assert!(s.contains(&format!("{two}old{two}")));
+    }
+
+    #[test]
+    fn errors_on_missing_or_unpaired_markers() {
+        let things = section("things", SortOrder::AsGiven);
+        let mut s = "# readme".to_string();
+        assert!(replace_section(&mut s, &things).is_err());
+        let mut s = marker("things").repeat(3);
+        assert!(replace_section(&mut s, &things).is_err());
+    }
+
+    #[test]
+    fn writes_files_atomically() {
+        let dir = env::temp_dir().join(format!("aoc-readme-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("README.md");
+        fs::write(&path, format!("{0}{0}", marker("things"))).unwrap();
+
+        update_file(&path, &[section("things", SortOrder::AsGiven)]).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("| c | 1s |"));
+
+        // a missing section leaves the file as it was.
+        let before = fs::read_to_string(&path).unwrap();
+        assert!(update_file(&path, &[section("missing", SortOrder::AsGiven)]).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // so does a file that is not valid UTF-8, rather than having its bad bytes replaced.
+        let bytes = [
+            marker("things").as_bytes(),
+            &[0xff],
+            marker("things").as_bytes(),
+        ]
+        .concat();
+        fs::write(&path, &bytes).unwrap();
+        assert!(update_file(&path, &[section("things", SortOrder::AsGiven)]).is_err());
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,34 +1,102 @@
 /// Module that updates the readme me with timing information.
 /// The approach taken is similar to how `aoc-readme-stars` handles this.
-use std::{fs, io};
-
+use crate::template::readme::{self, Align, Cell, Column, Section, SortOrder, Table};
 use crate::Day;
 
-static MARKER: &str = "<!--- benchmarking table --->";
+pub use crate::template::readme::Error;
+
+static MARKER: &str = "benchmarking table";
 
-#[derive(Debug)]
-pub enum Error {
-    Parser(String),
-    IO(io::Error),
+/// What happened when a part was run, as reported by its solution binary.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum PartStatus {
+    /// The part returned an answer. Holds the timing string if the part was benched.
+    Solved(Option<String>),
+    /// The part returned `None`.
+    #[default]
+    NotImplemented,
+    /// The part returned an error.
+    Failed,
+    /// The part panicked.
+    Panicked,
+    /// The part did not finish within the configured timeout.
+    TimedOut,
 }
 
-impl From<std::io::Error> for Error {
-    fn from(e: std::io::Error) -> Self {
-        Error::IO(e)
+impl PartStatus {
+    fn table_cell(&self) -> &str {
+        match self {
+            PartStatus::Solved(Some(timing)) => timing,
+            PartStatus::Solved(None) | PartStatus::NotImplemented => "-",
+            PartStatus::Failed => "✖",
+            PartStatus::Panicked => "💥",
+            PartStatus::TimedOut => "⏱",
+        }
+    }
+
+    /// The same status names as the runner's `--json` output.
+    pub fn status(&self) -> &'static str {
+        match self {
+            PartStatus::Solved(_) => "solved",
+            PartStatus::NotImplemented => "not_implemented",
+            PartStatus::Failed => "failed",
+            PartStatus::Panicked => "panicked",
+            PartStatus::TimedOut => "timed_out",
+        }
+    }
+
+    pub fn timing(&self) -> Option<&str> {
+        match self {
+            PartStatus::Solved(Some(timing)) => Some(timing),
+            _ => None,
+        }
+    }
+
+    /// How long the part took in nanoseconds, if it was benched.
+    pub fn nanos(&self) -> Option<f64> {
+        self.timing().and_then(parse_timing)
     }
 }
 
 #[derive(Clone)]
 pub struct Timings {
     pub day: Day,
-    pub part_1: Option<String>,
-    pub part_2: Option<String>,
+    pub part_1: PartStatus,
+    pub part_2: PartStatus,
     pub total_nanos: f64,
 }
 
-pub struct TablePosition {
-    pos_start: usize,
-    pos_end: usize,
+/// A column of the benchmark table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BenchmarkColumn {
+    Day,
+    Part1,
+    Part2,
+    /// Both parts together.
+    Total,
+}
+
+/// Which columns the benchmark table shows, and the order of its rows.
+/// `sort` refers to columns by their position in `columns`.
+#[derive(Clone, Debug)]
+pub struct Layout {
+    pub heading: String,
+    pub columns: Vec<BenchmarkColumn>,
+    pub sort: SortOrder,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            heading: "## Benchmarks".into(),
+            columns: vec![
+                BenchmarkColumn::Day,
+                BenchmarkColumn::Part1,
+                BenchmarkColumn::Part2,
+            ],
+            sort: SortOrder::Ascending(0),
+        }
+    }
 }
 
 #[must_use]
@@ -36,95 +104,119 @@ pub fn get_path_for_bin(day: Day) -> String {
     format!("./src/bin/{day}.rs")
 }
 
-fn locate_table(readme: &str) -> Result<TablePosition, Error> {
-    let matches: Vec<_> = readme.match_indices(MARKER).collect();
-
-    if matches.len() > 2 {
-        return Err(Error::Parser(
-            "{}: too many occurences of marker in README.".into(),
-        ));
+/// Reads a duration as printed by `Duration`'s `Debug` implementation (e.g. `74.13µs`) as nanoseconds.
+pub(crate) fn parse_timing(s: &str) -> Option<f64> {
+    // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
+    let parse_to_float = |postfix: &str| s.split(postfix).next()?.parse::<f64>().ok();
+    match s {
+        s if s.contains("ns") => parse_to_float("ns"),
+        s if s.contains("µs") => parse_to_float("µs").map(|x| x * 1000_f64),
+        s if s.contains("ms") => parse_to_float("ms").map(|x| x * 1_000_000_f64),
+        _ => parse_to_float("s").map(|x| x * 1_000_000_000_f64),
     }
-
-    let pos_start = matches
-        .first()
-        .map(|m| m.0)
-        .ok_or_else(|| Error::Parser("Could not find table start position.".into()))?;
-
-    let pos_end = matches
-        .last()
-        .map(|m| m.0 + m.1.len())
-        .ok_or_else(|| Error::Parser("Could not find table end position.".into()))?;
-
-    Ok(TablePosition { pos_start, pos_end })
 }
 
-fn construct_table(prefix: &str, timings: Vec<Timings>, total_millis: f64) -> String {
-    let header = format!("{prefix} Benchmarks");
+fn cell(timing: &Timings, column: BenchmarkColumn) -> Cell {
+    let part = |status: &PartStatus| {
+        let text = format!("`{}`", status.table_cell());
+        status
+            .nanos()
+            .map_or_else(|| Cell::text(&text), |nanos| Cell::keyed(&text, nanos))
+    };
+    match column {
+        BenchmarkColumn::Day => Cell::keyed(
+            format!(
+                "[Day {}]({})",
+                timing.day.into_inner(),
+                get_path_for_bin(timing.day)
+            ),
+            f64::from(timing.day.into_inner()),
+        ),
+        BenchmarkColumn::Part1 => part(&timing.part_1),
+        BenchmarkColumn::Part2 => part(&timing.part_2),
+        BenchmarkColumn::Total => Cell::keyed(
+            format!("`{:.2}ms`", timing.total_nanos / 1_000_000_f64),
+            timing.total_nanos,
+        ),
+    }
+}
 
-    let mut lines: Vec<String> = vec![
-        MARKER.into(),
-        header,
-        String::new(),
-        "| Day | Part 1 | Part 2 |".into(),
-        "| :---: | :---: | :---:  |".into(),
-    ];
+fn construct_section(
+    layout: &Layout,
+    timings: Vec<Timings>,
+    total_millis: f64,
+) -> Result<Section, Error> {
+    let columns = layout
+        .columns
+        .iter()
+        .map(|c| {
+            let header = match c {
+                BenchmarkColumn::Day => "Day",
+                BenchmarkColumn::Part1 => "Part 1",
+                BenchmarkColumn::Part2 => "Part 2",
+                BenchmarkColumn::Total => "Total",
+            };
+            Column::new(header, Align::Center)
+        })
+        .collect();
 
+    let mut table = Table::new(columns, layout.sort);
     for timing in timings {
-        let path = get_path_for_bin(timing.day);
-        lines.push(format!(
-            "| [Day {}]({}) | `{}` | `{}` |",
-            timing.day.into_inner(),
-            path,
-            timing.part_1.unwrap_or_else(|| "-".into()),
-            timing.part_2.unwrap_or_else(|| "-".into())
-        ));
+        table.push(layout.columns.iter().map(|c| cell(&timing, *c)).collect())?;
     }
 
-    lines.push(String::new());
-    lines.push(format!("**Total: {total_millis:.2}ms**"));
-    lines.push(MARKER.into());
-
-    lines.join("\n")
+    Ok(Section {
+        table: Some(table),
+        footer: vec![format!("**Total: {total_millis:.2}ms**")],
+        ..Section::new(MARKER, &layout.heading)
+    })
 }
 
-fn update_content(s: &mut String, timings: Vec<Timings>, total_millis: f64) -> Result<(), Error> {
-    let positions = locate_table(s)?;
-    let table = construct_table("##", timings, total_millis);
-    s.replace_range(positions.pos_start..positions.pos_end, &table);
-    Ok(())
+pub fn update(timings: Vec<Timings>, total_millis: f64) -> Result<(), Error> {
+    update_with_layout(&Layout::default(), timings, total_millis)
 }
 
-pub fn update(timings: Vec<Timings>, total_millis: f64) -> Result<(), Error> {
-    let path = "README.md";
-    let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
-    update_content(&mut readme, timings, total_millis)?;
-    fs::write(path, &readme)?;
-    Ok(())
+pub fn update_with_layout(
+    layout: &Layout,
+    timings: Vec<Timings>,
+    total_millis: f64,
+) -> Result<(), Error> {
+    readme::update(&[construct_section(layout, timings, total_millis)?])
 }
 
 #[cfg(feature = "test_lib")]
 mod tests {
-    use super::{update_content, Timings, MARKER};
+    use super::{construct_section, BenchmarkColumn, Error, Layout, PartStatus, Timings, MARKER};
     use crate::day;
+    use crate::template::readme::{marker, replace_section, SortOrder};
+
+    fn update_content(
+        s: &mut String,
+        timings: Vec<Timings>,
+        total_millis: f64,
+    ) -> Result<(), Error> {
+        let section = construct_section(&Layout::default(), timings, total_millis)?;
+        replace_section(s, &section)
+    }
 
     fn get_mock_timings() -> Vec<Timings> {
         vec![
             Timings {
                 day: day!(1),
-                part_1: Some("10ms".into()),
-                part_2: Some("20ms".into()),
+                part_1: PartStatus::Solved(Some("10ms".into())),
+                part_2: PartStatus::Solved(Some("20ms".into())),
                 total_nanos: 3e+10,
             },
             Timings {
                 day: day!(2),
-                part_1: Some("30ms".into()),
-                part_2: Some("40ms".into()),
+                part_1: PartStatus::Solved(Some("30ms".into())),
+                part_2: PartStatus::Solved(Some("40ms".into())),
                 total_nanos: 7e+10,
             },
             Timings {
                 day: day!(4),
-                part_1: Some("40ms".into()),
-                part_2: Some("50ms".into()),
+                part_1: PartStatus::Solved(Some("40ms".into())),
+                part_2: PartStatus::Solved(Some("50ms".into())),
                 total_nanos: 9e+10,
             },
         ]
@@ -140,29 +232,30 @@ mod tests {
     #[test]
     #[should_panic]
     fn errors_if_too_many_markers_present() {
-        let mut s = format!("{} {} {}", MARKER, MARKER, MARKER);
+        let m = marker(MARKER);
+        let mut s = format!("{} {} {}", m, m, m);
         update_content(&mut s, get_mock_timings(), 190.0).unwrap();
     }
 
     #[test]
     fn updates_empty_benchmarks() {
-        let mut s = format!("foo\nbar\n{}{}\nbaz", MARKER, MARKER);
+        let mut s = format!("foo\nbar\n{0}{0}\nbaz", marker(MARKER));
         update_content(&mut s, get_mock_timings(), 190.0).unwrap();
         assert_eq!(s.contains("## Benchmarks"), true);
     }
 
     #[test]
     fn updates_existing_benchmarks() {
-        let mut s = format!("foo\nbar\n{}{}\nbaz", MARKER, MARKER);
+        let mut s = format!("foo\nbar\n{0}{0}\nbaz", marker(MARKER));
         update_content(&mut s, get_mock_timings(), 190.0).unwrap();
         update_content(&mut s, get_mock_timings(), 190.0).unwrap();
-        assert_eq!(s.matches(MARKER).collect::<Vec<&str>>().len(), 2);
+        assert_eq!(s.matches(&marker(MARKER)).collect::<Vec<&str>>().len(), 2);
         assert_eq!(s.matches("## Benchmarks").collect::<Vec<&str>>().len(), 1);
     }
 
     #[test]
     fn format_benchmarks() {
-        let mut s = format!("foo\nbar\n{}\n{}\nbaz", MARKER, MARKER);
+        let mut s = format!("foo\nbar\n{0}\n{0}\nbaz", marker(MARKER));
         update_content(&mut s, get_mock_timings(), 190.0).unwrap();
         let expected = [
             "foo",
@@ -171,7 +264,7 @@ mod tests {
             "## Benchmarks",
             "",
             "| Day | Part 1 | Part 2 |",
-            "| :---: | :---: | :---:  |",
+            "| :---: | :---: | :---: |",
             "| [Day 1](./src/bin/01.rs) | `10ms` | `20ms` |",
             "| [Day 2](./src/bin/02.rs) | `30ms` | `40ms` |",
             "| [Day 4](./src/bin/04.rs) | `40ms` | `50ms` |",
@@ -183,4 +276,62 @@ mod tests {
         .join("\n");
         assert_eq!(s, expected);
     }
+
+    #[test]
+    fn format_unsolved_parts() {
+        let mut s = marker(MARKER).repeat(2);
+        let timings = vec![Timings {
+            day: day!(5),
+            part_1: PartStatus::NotImplemented,
+            part_2: PartStatus::Failed,
+            total_nanos: 0.0,
+        }];
+        update_content(&mut s, timings, 0.0).unwrap();
+        assert!(s.contains("| [Day 5](./src/bin/05.rs) | `-` | `✖` |"));
+    }
+
+    #[test]
+    fn custom_layout() {
+        let layout = Layout {
+            heading: "### Slowest days".into(),
+            columns: vec![BenchmarkColumn::Day, BenchmarkColumn::Total],
+            sort: SortOrder::Descending(1),
+        };
+        let section = construct_section(&layout, get_mock_timings(), 190.0).unwrap();
+        let mut s = marker(MARKER).repeat(2);
+        replace_section(&mut s, &section).unwrap();
+        let expected = [
+            "<!--- benchmarking table --->",
+            "### Slowest days",
+            "",
+            "| Day | Total |",
+            "| :---: | :---: |",
+            "| [Day 4](./src/bin/04.rs) | `90000.00ms` |",
+            "| [Day 2](./src/bin/02.rs) | `70000.00ms` |",
+            "| [Day 1](./src/bin/01.rs) | `30000.00ms` |",
+            "",
+            "**Total: 190.00ms**",
+            "<!--- benchmarking table --->",
+        ]
+        .join("\n");
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn sorts_parts_by_timing() {
+        let layout = Layout {
+            sort: SortOrder::Descending(1),
+            ..Layout::default()
+        };
+        let mut timings = get_mock_timings();
+        timings[0].part_1 = PartStatus::Solved(Some("1.5s".into()));
+        timings[1].part_1 = PartStatus::Failed;
+        let section = construct_section(&layout, timings, 0.0).unwrap();
+        let mut s = marker(MARKER).repeat(2);
+        replace_section(&mut s, &section).unwrap();
+        let day_1 = s.find("[Day 1]").unwrap();
+        let day_4 = s.find("[Day 4]").unwrap();
+        let day_2 = s.find("[Day 2]").unwrap();
+        assert!(day_1 < day_4 && day_4 < day_2);
+    }
 }
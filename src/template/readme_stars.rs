@@ -0,0 +1,180 @@
+/// Module that updates the readme with star progress, computed from the local answers store and
+/// the parts that solve.
+/// It writes the same table as `advent-readme-stars`, so either can maintain it.
+use std::fs;
+
+use crate::template::readme::{self, Align, Cell, Column, Error, Section, SortOrder, Table};
+use crate::Day;
+
+static MARKER: &str = "advent_readme_stars table";
+
+/// Which parts of a day have earned their star.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    pub day: Day,
+    pub part_1: bool,
+    pub part_2: bool,
+}
+
+impl Progress {
+    pub fn stars(&self) -> usize {
+        usize::from(self.part_1) + usize::from(self.part_2)
+    }
+}
+
+fn star_cell(earned: bool) -> Cell {
+    Cell::keyed(if earned { "⭐" } else { " " }, f64::from(u8::from(earned)))
+}
+
+fn construct_section(year: Option<u16>, progress: &[Progress]) -> Result<Section, Error> {
+    let heading = match year {
+        Some(year) => format!("## {year} Results"),
+        None => "## Results".into(),
+    };
+
+    let columns = ["Day", "Part 1", "Part 2"].map(|h| Column::new(h, Align::Center));
+    let mut table = Table::new(columns.to_vec(), SortOrder::Ascending(0));
+
+    for p in progress {
+        let day = p.day.into_inner();
+        let link = match year {
+            Some(year) => format!("https://adventofcode.com/{year}/day/{day}"),
+            None => format!("./src/bin/{}.rs", p.day),
+        };
+        table.push(vec![
+            Cell::keyed(format!("[Day {day}]({link})"), f64::from(day)),
+            star_cell(p.part_1),
+            star_cell(p.part_2),
+        ])?;
+    }
+
+    Ok(Section {
+        table: Some(table),
+        ..Section::new(MARKER, heading)
+    })
+}
+
+/// The stars the progress table in `readme` already shows, read from its rows.
+#[must_use]
+pub fn parse(readme: &str) -> Vec<Progress> {
+    let marker = readme::marker(MARKER);
+    let Some((_, rest)) = readme.split_once(&marker) else {
+        return vec![];
+    };
+    let section = rest
+        .split_once(&marker)
+        .map_or(rest, |(section, _)| section);
+
+    section
+        .lines()
+        .filter_map(|line| {
+            let cells: Vec<&str> = line.split('|').map(str::trim).collect();
+            let day = cells
+                .get(1)?
+                .strip_prefix("[Day ")?
+                .split(']')
+                .next()?
+                .parse()
+                .ok()?;
+            Some(Progress {
+                day: Day::new(day)?,
+                part_1: cells.get(2)?.contains('⭐'),
+                part_2: cells.get(3)?.contains('⭐'),
+            })
+        })
+        .collect()
+}
+
+/// The stars shown in the project's `README.md`, none if it cannot be read.
+#[must_use]
+pub fn read() -> Vec<Progress> {
+    fs::read_to_string("README.md")
+        .map(|readme| parse(&readme))
+        .unwrap_or_default()
+}
+
+pub fn update(year: Option<u16>, progress: &[Progress]) -> Result<(), Error> {
+    readme::update(&[construct_section(year, progress)?])
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{construct_section, parse, Error, Progress, MARKER};
+    use crate::day;
+    use crate::template::readme::{marker, replace_section};
+
+    fn update_content(
+        s: &mut String,
+        year: Option<u16>,
+        progress: &[Progress],
+    ) -> Result<(), Error> {
+        replace_section(s, &construct_section(year, progress)?)
+    }
+
+    fn get_mock_progress() -> Vec<Progress> {
+        vec![
+            Progress {
+                day: day!(1),
+                part_1: true,
+                part_2: true,
+            },
+            Progress {
+                day: day!(5),
+                part_1: true,
+                part_2: false,
+            },
+        ]
+    }
+
+    #[test]
+    #[should_panic]
+    fn errors_if_marker_not_present() {
+        let mut s = "# readme <!--- benchmarking table --->".to_string();
+        update_content(&mut s, Some(2023), &get_mock_progress()).unwrap();
+    }
+
+    #[test]
+    fn format_stars() {
+        let mut s = format!("foo\n{0}\n## old\n{0}\nbaz", marker(MARKER));
+        update_content(&mut s, Some(2023), &get_mock_progress()).unwrap();
+        let expected = [
+            "foo",
+            "<!--- advent_readme_stars table --->",
+            "## 2023 Results",
+            "",
+            "| Day | Part 1 | Part 2 |",
+            "| :---: | :---: | :---: |",
+            "| [Day 1](https://adventofcode.com/2023/day/1) | ⭐ | ⭐ |",
+            "| [Day 5](https://adventofcode.com/2023/day/5) | ⭐ |   |",
+            "<!--- advent_readme_stars table --->",
+            "baz",
+        ]
+        .join("\n");
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn updates_existing_stars() {
+        let mut s = format!("{0}{0}\n<!--- benchmarking table --->", marker(MARKER));
+        update_content(&mut s, None, &get_mock_progress()).unwrap();
+        update_content(&mut s, None, &get_mock_progress()).unwrap();
+        assert_eq!(s.matches(&marker(MARKER)).count(), 2);
+        assert_eq!(s.matches("## Results").count(), 1);
+        assert!(s.contains("| [Day 5](./src/bin/05.rs) | ⭐ |   |"));
+        assert!(s.ends_with("<!--- benchmarking table --->"));
+    }
+
+    #[test]
+    fn reads_shown_stars() {
+        let mut s = format!("{0}{0}", marker(MARKER));
+        update_content(&mut s, Some(2023), &get_mock_progress()).unwrap();
+        assert_eq!(parse(&s), get_mock_progress());
+        assert_eq!(parse("# readme without stars"), vec![]);
+    }
+
+    #[test]
+    fn counts_stars() {
+        let stars: usize = get_mock_progress().iter().map(Progress::stars).sum();
+        assert_eq!(stars, 3);
+    }
+}
@@ -1,86 +1,599 @@
 /// Encapsulates code that interacts with solution functions.
-use crate::template::{aoc_cli, ANSI_ITALIC, ANSI_RESET};
-use crate::Day;
+use crate::template::answer::{self, json_string, Answer};
+use crate::template::{aoc_cli, cpu_profile, ANSI_ITALIC, ANSI_RESET};
+use crate::{ocr, trace, viz, Day};
+use std::any::Any;
 use std::fmt::Display;
 use std::io::{stdout, Write};
+use std::panic::{self, AssertUnwindSafe};
 use std::process::Output;
+use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
-use std::{cmp, env, process};
+use std::{cmp, env, process, thread};
 
 use super::ANSI_BOLD;
 
-pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
-    let part_str = format!("Part {part}");
+/// Solutions run on their own thread, give it a generous stack for deeply recursive solutions.
+pub(crate) const SOLUTION_STACK_SIZE: usize = 256 * 1024 * 1024;
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+/// The outcome of running a solution part once.
+pub enum Outcome<T> {
+    Solved(T),
+    NotImplemented,
+    Failed(String),
+    Panicked(String),
+    TimedOut(Duration),
+}
+
+impl<T> Outcome<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        match self {
+            Outcome::Solved(x) => Outcome::Solved(f(x)),
+            Outcome::NotImplemented => Outcome::NotImplemented,
+            Outcome::Failed(e) => Outcome::Failed(e),
+            Outcome::Panicked(e) => Outcome::Panicked(e),
+            Outcome::TimedOut(t) => Outcome::TimedOut(t),
+        }
+    }
+
+    /// The solved value, or what went wrong as the outcome of something else.
+    fn solved<U>(self) -> Result<T, Outcome<U>> {
+        match self {
+            Outcome::Solved(x) => Ok(x),
+            Outcome::NotImplemented => Err(Outcome::NotImplemented),
+            Outcome::Failed(e) => Err(Outcome::Failed(e)),
+            Outcome::Panicked(e) => Err(Outcome::Panicked(e)),
+            Outcome::TimedOut(t) => Err(Outcome::TimedOut(t)),
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        match self {
+            Outcome::Solved(_) => "solved",
+            Outcome::NotImplemented => "not_implemented",
+            Outcome::Failed(_) => "failed",
+            Outcome::Panicked(_) => "panicked",
+            Outcome::TimedOut(_) => "timed_out",
+        }
+    }
+}
+
+/// A value that can be returned from a solution part.
+///
+/// `None` means the part has not been implemented yet, while an `Err` means the part ran but failed.
+/// Errors are rendered with their alternate format, which prints the whole context chain for
+/// [`anyhow::Error`]s.
+pub trait PartResult {
+    type Output: Into<Answer>;
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    fn into_outcome(self) -> Outcome<Self::Output>;
+}
+
+impl<T: Into<Answer>> PartResult for Option<T> {
+    type Output = T;
+
+    fn into_outcome(self) -> Outcome<T> {
+        match self {
+            Some(answer) => Outcome::Solved(answer),
+            None => Outcome::NotImplemented,
+        }
+    }
+}
+
+impl<T: Into<Answer>, E: Display> PartResult for Result<T, E> {
+    type Output = T;
+
+    fn into_outcome(self) -> Outcome<T> {
+        match self {
+            Ok(answer) => Outcome::Solved(answer),
+            Err(e) => Outcome::Failed(format!("{e:#}")),
+        }
+    }
+}
 
-    if let Some(result) = result {
+pub fn run_part<I, R>(func: impl Fn(I) -> R + Send + Sync + 'static, input: I, day: Day, part: u8)
+where
+    I: Clone + Send + 'static,
+    R: PartResult,
+{
+    run_labelled(
+        Arc::new(move |input| outcome_of(func(input))),
+        input,
+        day,
+        part,
+        &format!("Part {part}"),
+    );
+}
+
+fn run_labelled<I>(
+    func: Arc<dyn Fn(I) -> Outcome<Answer> + Send + Sync>,
+    input: I,
+    day: Day,
+    part: u8,
+    part_str: &str,
+) where
+    I: Clone + Send + 'static,
+{
+    let is_json = is_json_output();
+
+    let func: Arc<dyn Fn(I) -> Outcome<Answer> + Send + Sync> = if trace::ENABLED {
+        trace::reset();
+        Arc::new(move |input| trace::in_span(trace::PART, || func(input)))
+    } else {
+        func
+    };
+
+    viz::begin(day, part);
+    let (result, duration, samples) = run_timed(Arc::clone(&func), input.clone(), |result| {
+        if !is_json {
+            print_result(result, part_str, "");
+        }
+    });
+    viz::end();
+
+    let expected = answer::read_expected(day, part);
+
+    if is_json {
+        println!(
+            "{}",
+            result_to_json(&result, day, part, &duration, samples, expected.as_ref())
+        );
+    } else {
+        print_result(&result, part_str, &format_duration(&duration, samples));
+        print_verdict(&result, expected.as_ref());
+        if let Some(report) = trace::report() {
+            print!("{report}");
+        }
+    }
+
+    if cpu_profile::is_profile_cpu() && matches!(result, Outcome::Solved(_)) {
+        match cpu_profile::run(func, input, &duration, day, part) {
+            Ok(profile) if !is_json => println!(
+                "  flamegraph of {} samples over {} runs: {} ({})",
+                profile.samples,
+                profile.runs,
+                profile.svg.display(),
+                profile.folded.display()
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("Could not profile part {part}: {e:#}"),
+        }
+    }
+
+    if let Outcome::Solved(result) = result {
         submit_result(result, day, part);
     }
 }
 
+/// Converts what a part returns to an [`Outcome`], so parts with different return types can be
+/// listed together as [`Variant`]s.
+pub fn outcome_of<R: PartResult>(result: R) -> Outcome<Answer> {
+    result.into_outcome().map(Into::into)
+}
+
+/// A named alternative implementation of a part, e.g. a brute force kept to check a faster one.
+pub struct Variant {
+    pub part: u8,
+    pub name: &'static str,
+    pub func: fn(&str) -> Outcome<Answer>,
+}
+
+/// The variant picked with `--variant <name>` for `part`, if the day has one by that name.
+/// Exits if none of the day's parts has a variant called `name`.
+pub fn selected_variant(variants: &[Variant], part: u8, day: Day) -> Option<&Variant> {
+    let name = arg_value("--variant")?;
+    if !variants.iter().any(|v| v.name == name) {
+        let known: Vec<String> = variants
+            .iter()
+            .map(|v| format!("{} (part {})", v.name, v.part))
+            .collect();
+        if known.is_empty() {
+            eprintln!("Day {day} has no variants.");
+        } else {
+            eprintln!(
+                "Day {day} has no variant `{name}`, it has: {}.",
+                known.join(", ")
+            );
+        }
+        process::exit(1);
+    }
+    variants.iter().find(|v| v.part == part && v.name == name)
+}
+
+pub fn run_variant(variant: &Variant, input: &'static str, day: Day) {
+    run_labelled(
+        Arc::new(variant.func),
+        input,
+        day,
+        variant.part,
+        &format!("Part {} ({})", variant.part, variant.name),
+    );
+}
+
+/// `--compare` runs every variant next to the part it replaces instead of running the parts.
+pub fn is_compare() -> bool {
+    env::args().any(|x| x == "--compare")
+}
+
+/// `--time` benches solved parts instead of timing a single run.
+fn is_time() -> bool {
+    env::args().any(|x| x == "--time")
+}
+
+/// Runs each part that has variants once per implementation, benching the ones that solve it
+/// with `--time`, and prints whether they agree and how their timings compare. Once a variant
+/// times out nothing is benched anymore, as it may still be running. Exits with an error if they
+/// do not agree.
+pub fn run_compare(defaults: [Variant; 2], variants: &[Variant], input: &'static str, day: Day) {
+    if variants.is_empty() {
+        eprintln!("Day {day} has no variants to compare.");
+        process::exit(1);
+    }
+
+    let mut all_agree = true;
+    let mut timed_out = false;
+    for default in &defaults {
+        let candidates: Vec<&Variant> = std::iter::once(default)
+            .chain(variants.iter().filter(|v| v.part == default.part))
+            .collect();
+        if candidates.len() == 1 {
+            continue;
+        }
+
+        println!("{ANSI_BOLD}Part {}{ANSI_RESET}", default.part);
+        let rows: Vec<Comparison> = candidates
+            .iter()
+            .map(|v| {
+                print!("  {ANSI_ITALIC}running {}{ANSI_RESET}", v.name);
+                let _ = stdout().flush();
+                let func: Arc<dyn Fn(&'static str) -> Outcome<Answer> + Send + Sync> =
+                    Arc::new(v.func);
+                let (result, base_time) = run_isolated(Arc::clone(&func), input, get_timeout());
+                let (result, duration, samples) =
+                    if matches!(result, Outcome::Solved(_)) && is_time() && !timed_out {
+                        match bench(func, input, &base_time) {
+                            Ok((duration, samples)) => (result, duration, samples),
+                            Err(failure) => (failure, base_time, 1),
+                        }
+                    } else {
+                        (result, base_time, 1)
+                    };
+                timed_out |= matches!(result, Outcome::TimedOut(_));
+                // clear the progress line before the table is printed.
+                print!("\r\x1b[2K");
+                Comparison {
+                    name: v.name,
+                    result,
+                    duration,
+                    samples,
+                }
+            })
+            .collect();
+
+        let (table, agree) = comparison_table(&rows);
+        print!("{table}");
+        if let Some(solved) = rows.iter().find(|r| matches!(r.result, Outcome::Solved(_))) {
+            print_verdict(
+                &solved.result,
+                answer::read_expected(day, default.part).as_ref(),
+            );
+        }
+        all_agree &= agree;
+    }
+
+    if !all_agree {
+        process::exit(1);
+    }
+}
+
+struct Comparison {
+    name: &'static str,
+    result: Outcome<Answer>,
+    duration: Duration,
+    samples: u128,
+}
+
+/// Lays out the results of a part's implementations side by side, with each timing relative to
+/// the fastest. The implementations agree if every one of them solved the part with the same answer.
+fn comparison_table(rows: &[Comparison]) -> (String, bool) {
+    let answers: Vec<String> = rows
+        .iter()
+        .map(|r| match &r.result {
+            Outcome::Solved(answer) => answer.submission(),
+            Outcome::NotImplemented => "✖ not implemented".into(),
+            Outcome::Failed(e) => format!("✖ failed: {e}"),
+            Outcome::Panicked(e) => format!("✖ panicked: {e}"),
+            Outcome::TimedOut(t) => format!("✖ timed out after {t:.1?}"),
+        })
+        .collect();
+    let fastest = rows
+        .iter()
+        .filter(|r| matches!(r.result, Outcome::Solved(_)))
+        .map(|r| r.duration)
+        .min();
+
+    let name_width = rows
+        .iter()
+        .map(|r| r.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let answer_width = answers.iter().map(|a| a.chars().count()).max().unwrap_or(0);
+
+    let mut res = String::new();
+    for (row, answer) in rows.iter().zip(&answers) {
+        let timing = match (&row.result, fastest) {
+            (Outcome::Solved(_), Some(fastest)) => format!(
+                "{}  {:.2}x",
+                format_duration(&row.duration, row.samples).trim(),
+                row.duration.as_secs_f64() / fastest.as_secs_f64().max(f64::MIN_POSITIVE)
+            ),
+            _ => String::new(),
+        };
+        res.push_str(
+            format!(
+                "  {:name_width$}  {answer:answer_width$}  {timing}",
+                row.name
+            )
+            .trim_end(),
+        );
+        res.push('\n');
+    }
+
+    let solved: Vec<&Answer> = rows
+        .iter()
+        .filter_map(|r| match &r.result {
+            Outcome::Solved(answer) => Some(answer),
+            _ => None,
+        })
+        .collect();
+    let agree = solved.len() == rows.len() && solved.windows(2).all(|w| w[0] == w[1]);
+    res.push_str(if agree {
+        "  ✔ all implementations agree\n"
+    } else {
+        "  ✖ the implementations do not agree\n"
+    });
+
+    (res, agree)
+}
+
+/// A day's report on how it reached its answers, e.g. a table of intermediate results.
+pub type Explain = fn(&str) -> anyhow::Result<String>;
+
+/// `--explain` prints the day's report instead of running its parts.
+pub fn is_explain() -> bool {
+    env::args().any(|x| x == "--explain")
+}
+
+pub fn run_explain(explain: Option<Explain>, input: &str, day: Day) {
+    let Some(explain) = explain else {
+        eprintln!("Day {day} has nothing to explain.");
+        process::exit(1);
+    };
+    match explain(input) {
+        Ok(report) => println!("{report}"),
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Structured output is requested with the `--json` flag. Each part then prints a single JSON
+/// object instead of the human-readable lines.
+fn is_json_output() -> bool {
+    env::args().any(|x| x == "--json")
+}
+
+fn result_to_json(
+    result: &Outcome<Answer>,
+    day: Day,
+    part: u8,
+    duration: &Duration,
+    samples: u128,
+    expected: Option<&Answer>,
+) -> String {
+    let answer = match result {
+        Outcome::Solved(answer) => answer.to_json(),
+        _ => "null".into(),
+    };
+    let error = match result {
+        Outcome::Failed(e) | Outcome::Panicked(e) => json_string(e),
+        _ => "null".into(),
+    };
+    let correct = match (result, expected) {
+        (Outcome::Solved(answer), Some(expected)) => (answer == expected).to_string(),
+        _ => "null".into(),
+    };
+    format!(
+        "{{\"day\":{},\"part\":{part},\"status\":\"{}\",\"answer\":{answer},\"error\":{error},\"nanos\":{},\"samples\":{samples},\"correct\":{correct}}}",
+        day.into_inner(),
+        result.status(),
+        duration.as_nanos(),
+    )
+}
+
+/// Compares a solved part with the stored answer in `data/answers`, if there is one.
+fn print_verdict(result: &Outcome<Answer>, expected: Option<&Answer>) {
+    match (result, expected) {
+        (Outcome::Solved(answer), Some(expected)) if answer == expected => {
+            println!("  ✔ matches the stored answer");
+        }
+        (Outcome::Solved(_), Some(expected)) => {
+            println!(
+                "  ✖ does not match the stored answer {ANSI_BOLD}{}{ANSI_RESET}",
+                expected.submission()
+            );
+        }
+        _ => {}
+    }
+}
+
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
 ///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
-fn run_timed<I: Clone, T>(
-    func: impl Fn(I) -> T,
+///
+/// The first execution is isolated, see [`run_isolated`], and so is the bench. If the part panics or
+/// times out while it is benched, that is its outcome.
+fn run_timed<I, T>(
+    func: Arc<dyn Fn(I) -> Outcome<T> + Send + Sync>,
     input: I,
-    hook: impl Fn(&T),
-) -> (T, Duration, u128) {
-    let timer = Instant::now();
-    let result = {
-        let input = input.clone();
-
+    hook: impl Fn(&Outcome<T>),
+) -> (Outcome<T>, Duration, u128)
+where
+    I: Clone + Send + 'static,
+    T: Send + 'static,
+{
+    let (result, base_time) = {
         #[cfg(feature = "dhat-heap")]
         let _profiler = dhat::Profiler::new_heap();
 
-        func(input)
+        run_isolated(Arc::clone(&func), input.clone(), get_timeout())
     };
-    let base_time = timer.elapsed();
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    // only solved parts are benched, there is nothing to measure for a failure.
+    let is_solved = matches!(result, Outcome::Solved(_));
+    if is_solved && is_time() {
+        match bench(func, input, &base_time) {
+            Ok((duration, samples)) => (result, duration, samples),
+            Err(failure) => (failure, base_time, 1),
+        }
     } else {
-        (base_time, 1)
-    };
+        (result, base_time, 1)
+    }
+}
+
+/// Runs a solution part once on a separate thread, catching panics.
+/// If a timeout is set and the part does not finish in time, it is reported as timed out and left
+/// running in the background - it is torn down when the process exits.
+pub(crate) fn run_isolated<I, T>(
+    func: Arc<dyn Fn(I) -> Outcome<T> + Send + Sync>,
+    input: I,
+    timeout: Option<Duration>,
+) -> (Outcome<T>, Duration)
+where
+    I: Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+
+    let spawned = thread::Builder::new()
+        .name("solution".into())
+        .stack_size(SOLUTION_STACK_SIZE)
+        .spawn(move || {
+            let timer = Instant::now();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| func(input)));
+            let elapsed = timer.elapsed();
+            let outcome =
+                result.unwrap_or_else(|payload| Outcome::Panicked(panic_message(&*payload)));
+            // the receiver is gone if the part timed out, nothing left to do then.
+            let _ = tx.send((outcome, elapsed));
+        });
+
+    if let Err(e) = spawned {
+        return (
+            Outcome::Failed(format!("could not spawn solution thread: {e}")),
+            Duration::ZERO,
+        );
+    }
+
+    match timeout {
+        Some(timeout) => rx
+            .recv_timeout(timeout)
+            .unwrap_or((Outcome::TimedOut(timeout), timeout)),
+        None => rx.recv().unwrap_or_else(|_| {
+            (
+                Outcome::Panicked("solution thread exited unexpectedly".into()),
+                Duration::ZERO,
+            )
+        }),
+    }
+}
 
-    (result, run.0, run.1)
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".into()
+    }
 }
 
-fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
-    let mut stdout = stdout();
+/// The value that follows `flag` on the command line.
+pub(crate) fn arg_value(flag: &str) -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    let ix = args.iter().position(|x| x == flag)?;
+    args.get(ix + 1).cloned()
+}
 
-    print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
-    let _ = stdout.flush();
+/// Reads the per-part timeout from the `--timeout <seconds>` argument, falling back to the
+/// `AOC_TIMEOUT` environment variable. Parts run without a timeout if neither is set.
+pub(crate) fn get_timeout() -> Option<Duration> {
+    let secs = arg_value("--timeout").or_else(|| env::var("AOC_TIMEOUT").ok())?;
 
-    let bench_iterations = cmp::min(
-        10000,
-        cmp::max(
-            Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10),
-            10,
-        ),
-    );
+    match secs.parse::<f64>() {
+        Ok(secs) if secs > 0_f64 => Some(Duration::from_secs_f64(secs)),
+        _ => {
+            eprintln!(
+                "Ignoring invalid timeout \"{secs}\", expecting a positive number of seconds."
+            );
+            None
+        }
+    }
+}
 
-    let mut timers: Vec<Duration> = vec![];
+/// Runs a solved part about a second's worth of times and returns the average time and how often it
+/// ran. The runs are isolated like [`run_isolated`] and together get the timeout once per run, so a
+/// part that panics or hangs while it is benched is reported instead of taking down the process.
+fn bench<I, T>(
+    func: Arc<dyn Fn(I) -> Outcome<T> + Send + Sync>,
+    input: I,
+    base_time: &Duration,
+) -> Result<(Duration, u128), Outcome<T>>
+where
+    I: Clone + Send + 'static,
+    T: Send + 'static,
+{
+    let mut stdout = stdout();
 
-    for _ in 0..bench_iterations {
-        // need a clone here to make the borrow checker happy.
-        let cloned = input.clone();
-        let timer = Instant::now();
-        func(cloned);
-        timers.push(timer.elapsed());
+    if !is_json_output() {
+        print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
+        let _ = stdout.flush();
     }
 
-    (
+    let bench_iterations = bench_iterations(base_time);
+    let timeout = get_timeout()
+        .map(|t| t.saturating_mul(u32::try_from(bench_iterations).unwrap_or(u32::MAX)));
+
+    let runs = Arc::new(move |input: I| {
+        let mut timers: Vec<Duration> = vec![];
+        for _ in 0..bench_iterations {
+            // need a clone here to make the borrow checker happy.
+            let cloned = input.clone();
+            let timer = Instant::now();
+            if let Err(failure) = func(cloned).solved::<u128>() {
+                return failure;
+            }
+            timers.push(timer.elapsed());
+        }
+        Outcome::Solved(average_duration(&timers))
+    });
+    let (outcome, _) = run_isolated(runs, input, timeout);
+    let average = outcome.solved()?;
+
+    Ok((
         #[allow(clippy::cast_possible_truncation)]
-        Duration::from_nanos(average_duration(&timers) as u64),
+        Duration::from_nanos(average as u64),
         bench_iterations,
-    )
+    ))
+}
+
+/// How often a part that took `base_time` once is run when benching it: about a second's worth,
+/// but at least 10 and at most 10000 times.
+pub(crate) fn bench_iterations(base_time: &Duration) -> u128 {
+    (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000)
 }
 
 fn average_duration(numbers: &[Duration]) -> u128 {
@@ -99,13 +612,17 @@ fn format_duration(duration: &Duration, samples: u128) -> String {
     }
 }
 
-fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
+fn print_result(result: &Outcome<Answer>, part: &str, duration_str: &str) {
     let is_intermediate_result = duration_str.is_empty();
 
     match result {
-        Some(result) => {
-            if result.to_string().contains('\n') {
-                let str = format!("{part}: ▼ {duration_str}");
+        Outcome::Solved(result) => {
+            if let Answer::Grid(grid) = result {
+                // grids that spell out letters are shown together with the decoded text.
+                let decoded = ocr::decode(grid)
+                    .map(|d| format!("{ANSI_BOLD}{d}{ANSI_RESET} "))
+                    .unwrap_or_default();
+                let str = format!("{part}: ▼ {decoded}{duration_str}");
                 if is_intermediate_result {
                     print!("{str}");
                 } else {
@@ -123,7 +640,7 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
                 }
             }
         }
-        None => {
+        Outcome::NotImplemented => {
             if is_intermediate_result {
                 print!("{part}: ✖");
             } else {
@@ -131,14 +648,38 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
                 println!("{part}: ✖             ");
             }
         }
+        Outcome::Failed(err) => {
+            if is_intermediate_result {
+                print!("{part}: ✖ failed");
+            } else {
+                print!("\r");
+                println!("{part}: ✖ failed: {err}");
+            }
+        }
+        Outcome::Panicked(msg) => {
+            if is_intermediate_result {
+                print!("{part}: ✖ panicked");
+            } else {
+                print!("\r");
+                println!("{part}: ✖ panicked: {msg}");
+            }
+        }
+        Outcome::TimedOut(timeout) => {
+            if is_intermediate_result {
+                print!("{part}: ✖ timed out");
+            } else {
+                print!("\r");
+                println!("{part}: ✖ timed out after {timeout:.1?}");
+            }
+        }
     }
 }
 
 /// Parse the arguments passed to `solve` and try to submit one part of the solution if:
 ///  1. we are in `--release` mode.
 ///  2. aoc-cli is installed.
-fn submit_result<T: Display>(
-    result: T,
+fn submit_result(
+    result: Answer,
     day: Day,
     part: u8,
 ) -> Option<Result<Output, aoc_cli::AocCommandError>> {
@@ -170,5 +711,125 @@ fn submit_result<T: Display>(
     }
 
     println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    Some(aoc_cli::submit(day, part, &result.submission()))
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{bench, comparison_table, result_to_json, run_isolated, Comparison, Outcome};
+    use crate::{day, template::answer::Answer};
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn captures_panics() {
+        let (result, _) = run_isolated(
+            Arc::new(|x: u32| -> Outcome<u32> { panic!("bad input {x}") }),
+            7,
+            None,
+        );
+        assert!(matches!(result, Outcome::Panicked(msg) if msg == "bad input 7"));
+    }
+
+    #[test]
+    fn times_out_slow_parts() {
+        let (result, duration) = run_isolated(
+            Arc::new(|_: ()| {
+                thread::sleep(Duration::from_secs(5));
+                Outcome::Solved(1)
+            }),
+            (),
+            Some(Duration::from_millis(50)),
+        );
+        assert!(matches!(result, Outcome::TimedOut(_)));
+        assert_eq!(duration, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn reports_panics_while_benching() {
+        let calls = AtomicU32::new(0);
+        let result = bench(
+            Arc::new(move |x: u32| {
+                if calls.fetch_add(1, Ordering::Relaxed) == 3 {
+                    panic!("bad run {x}");
+                }
+                Outcome::Solved(x)
+            }),
+            7,
+            &Duration::from_millis(1),
+        );
+        assert!(matches!(result, Err(Outcome::Panicked(msg)) if msg == "bad run 7"));
+    }
+
+    #[test]
+    fn returns_solved_parts() {
+        let (result, _) = run_isolated(
+            Arc::new(|x: u32| Outcome::Solved(x * 2)),
+            21,
+            Some(Duration::from_secs(5)),
+        );
+        assert!(matches!(result, Outcome::Solved(42)));
+    }
+
+    #[test]
+    fn serialises_results() {
+        let json = result_to_json(
+            &Outcome::Solved(Answer::from(142_u32)),
+            day!(1),
+            2,
+            &Duration::from_nanos(1500),
+            10,
+            Some(&Answer::parse("142")),
+        );
+        assert_eq!(
+            json,
+            r#"{"day":1,"part":2,"status":"solved","answer":{"type":"int","value":142,"submission":"142"},"error":null,"nanos":1500,"samples":10,"correct":true}"#
+        );
+
+        let json = result_to_json(
+            &Outcome::Failed("line 2: bad".into()),
+            day!(1),
+            1,
+            &Duration::ZERO,
+            1,
+            None,
+        );
+        assert!(json.contains(r#""status":"failed","answer":null,"error":"line 2: bad""#));
+    }
+
+    #[test]
+    fn compares_implementations() {
+        let row = |name, result, millis| Comparison {
+            name,
+            result,
+            duration: Duration::from_millis(millis),
+            samples: 10,
+        };
+        let (table, agree) = comparison_table(&[
+            row("default", Outcome::Solved(Answer::from(46_u32)), 2),
+            row("brute", Outcome::Solved(Answer::from("46")), 8),
+        ]);
+        assert!(agree);
+        assert_eq!(
+            table,
+            "  default  46  (2.0ms @ 10 samples)  1.00x\n\
+            \x20 brute    46  (8.0ms @ 10 samples)  4.00x\n\
+            \x20 ✔ all implementations agree\n"
+        );
+
+        let (table, agree) = comparison_table(&[
+            row("default", Outcome::Solved(Answer::from(46_u32)), 2),
+            row("slow", Outcome::TimedOut(Duration::from_secs(1)), 1000),
+            row("brute", Outcome::Solved(Answer::from(47_u32)), 8),
+        ]);
+        assert!(!agree);
+        assert!(table.contains("  slow     ✖ timed out after 1.0s\n"));
+        assert!(table.ends_with("  ✖ the implementations do not agree\n"));
+    }
 }
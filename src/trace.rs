@@ -0,0 +1,257 @@
+/// Times the phases of a solution, e.g. parsing, building a graph and searching it.
+///
+/// A solution marks a phase by holding on to a [`span`] while it runs:
+/// `let _span = trace::span("parse");`. Spans nest, and after each part the runner prints how
+/// often every span was entered and how much time it took per run of the part.
+///
+/// Tracing is only compiled in with the `trace` feature (`cargo solve <day> --trace`). Without it a
+/// span is an empty value that does nothing, so marked solutions run and benchmark as before.
+use std::time::Duration;
+
+/// Whether spans are recorded in this build.
+pub const ENABLED: bool = cfg!(feature = "trace");
+
+/// The root span the runner puts around every run of a part.
+pub(crate) const PART: &str = "part";
+
+/// Marks the phase `name` until the returned value is dropped.
+#[must_use = "the span ends when this value is dropped"]
+#[inline(always)]
+pub fn span(name: &'static str) -> Span {
+    Span::enter(name)
+}
+
+/// Runs `f` inside the span `name`.
+#[inline(always)]
+pub fn in_span<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let _span = span(name);
+    f()
+}
+
+#[cfg(not(feature = "trace"))]
+pub struct Span;
+
+#[cfg(not(feature = "trace"))]
+impl Span {
+    #[inline(always)]
+    fn enter(_name: &'static str) -> Self {
+        Span
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+fn take_stats() -> Vec<Stat> {
+    vec![]
+}
+
+#[cfg(feature = "trace")]
+pub use recording::Span;
+#[cfg(feature = "trace")]
+use recording::take_stats;
+
+#[cfg(feature = "trace")]
+mod recording {
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    use hashbrown::HashMap;
+
+    use super::Stat;
+
+    thread_local! {
+        /// The names of the spans open on this thread, outermost first.
+        static STACK: RefCell<Vec<&'static str>> = const { RefCell::new(vec![]) };
+    }
+
+    static STATS: Mutex<Option<HashMap<Vec<&'static str>, Stat>>> = Mutex::new(None);
+
+    pub struct Span {
+        start: Instant,
+    }
+
+    impl Span {
+        #[inline(always)]
+        pub(super) fn enter(name: &'static str) -> Self {
+            STACK.with(|s| s.borrow_mut().push(name));
+            Span {
+                start: Instant::now(),
+            }
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            let elapsed = self.start.elapsed();
+            STACK.with(|s| {
+                let mut stack = s.borrow_mut();
+                let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+                let stats = stats.get_or_insert_with(HashMap::new);
+                match stats.get_mut(stack.as_slice()) {
+                    Some(stat) => {
+                        stat.calls += 1;
+                        stat.total += elapsed;
+                    }
+                    None => {
+                        stats.insert(
+                            stack.clone(),
+                            Stat {
+                                path: stack.clone(),
+                                calls: 1,
+                                total: elapsed,
+                            },
+                        );
+                    }
+                }
+                stack.pop();
+            });
+        }
+    }
+
+    pub(super) fn take_stats() -> Vec<Stat> {
+        let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        stats
+            .take()
+            .map(|s| s.into_values().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// How often a span was entered and for how long, keyed by its name and the names of the spans
+/// around it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stat {
+    pub path: Vec<&'static str>,
+    pub calls: u64,
+    pub total: Duration,
+}
+
+/// Forgets everything recorded so far.
+pub(crate) fn reset() {
+    take_stats();
+}
+
+/// The time and call breakdown of everything recorded since the last [`reset`], which is then
+/// forgotten. `None` if there is nothing to show, always the case without the `trace` feature.
+pub(crate) fn report() -> Option<String> {
+    render(&take_stats())
+}
+
+/// Lays `stats` out as an indented tree. Times and calls are per run of the part, which is how
+/// often the root [`PART`] span was entered. The share is of the part's time.
+fn render(stats: &[Stat]) -> Option<String> {
+    let (runs, part_time) = stats
+        .iter()
+        .find(|s| s.path == [PART])
+        .map_or((1, None), |s| (s.calls.max(1), Some(s.total)));
+    // the part on its own says nothing the runner has not printed already.
+    if stats.iter().all(|s| s.path == [PART]) {
+        return None;
+    }
+
+    let mut res = format!(
+        "  {:<32} {:>10} {:>12} {:>7}\n",
+        "span", "calls/run", "time/run", "share"
+    );
+    render_children(stats, &[], runs, part_time, &mut res);
+    Some(res)
+}
+
+fn render_children(
+    stats: &[Stat],
+    parent: &[&'static str],
+    runs: u64,
+    part_time: Option<Duration>,
+    res: &mut String,
+) {
+    let mut children: Vec<&Stat> = stats
+        .iter()
+        .filter(|s| s.path.len() == parent.len() + 1 && s.path.starts_with(parent))
+        .collect();
+    // the phases that take longest come first, which is what a breakdown is read for.
+    children.sort_by(|a, b| b.total.cmp(&a.total).then(a.path.cmp(&b.path)));
+
+    for stat in children {
+        let name = format!("{}{}", "  ".repeat(parent.len()), stat.path[parent.len()]);
+        let calls = stat.calls as f64 / runs as f64;
+        let time = stat.total / u32::try_from(runs).unwrap_or(u32::MAX);
+        let share = part_time
+            .filter(|t| !t.is_zero())
+            .map(|t| format!("{:.1}%", 100.0 * stat.total.as_secs_f64() / t.as_secs_f64()))
+            .unwrap_or_default();
+        res.push_str(&format!(
+            "  {name:<32} {:>10} {:>12} {share:>7}\n",
+            format_calls(calls),
+            format!("{time:.1?}"),
+        ));
+        render_children(stats, &stat.path, runs, part_time, res);
+    }
+}
+
+fn format_calls(calls: f64) -> String {
+    if calls.fract() == 0.0 {
+        format!("{calls}")
+    } else {
+        format!("{calls:.1}")
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{render, Stat, PART};
+    use std::time::Duration;
+
+    fn stat(path: &[&'static str], calls: u64, millis: u64) -> Stat {
+        Stat {
+            path: path.to_vec(),
+            calls,
+            total: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn renders_a_tree_per_run() {
+        let stats = [
+            stat(&[PART, "parse"], 2, 20),
+            stat(&[PART], 2, 200),
+            stat(&[PART, "search", "step"], 3000, 120),
+            stat(&[PART, "search"], 2, 150),
+        ];
+        let report = render(&stats).unwrap();
+        let lines: Vec<&str> = report.lines().map(str::trim_end).collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("  part "));
+        assert!(lines[1].ends_with("1      100.0ms  100.0%"));
+        assert!(lines[2].starts_with("    search "));
+        assert!(lines[2].ends_with("1       75.0ms   75.0%"));
+        assert!(lines[3].starts_with("      step "));
+        assert!(lines[3].ends_with("1500       60.0ms   60.0%"));
+        assert!(lines[4].starts_with("    parse "));
+        assert!(lines[4].ends_with("10.0ms   10.0%"));
+    }
+
+    #[test]
+    fn skips_parts_without_spans() {
+        assert_eq!(render(&[]), None);
+        assert_eq!(render(&[stat(&[PART], 1, 5)]), None);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn records_nested_spans() {
+        use super::{in_span, span, take_stats};
+        super::reset();
+        std::thread::spawn(|| {
+            let _part = span(PART);
+            for _ in 0..3 {
+                in_span("inner", || {});
+            }
+        })
+        .join()
+        .unwrap();
+        let mut stats = take_stats();
+        stats.sort_by_key(|s| s.path.len());
+        let calls: Vec<(Vec<&str>, u64)> = stats.into_iter().map(|s| (s.path, s.calls)).collect();
+        assert_eq!(calls, [(vec![PART], 1), (vec![PART, "inner"], 3)]);
+    }
+}
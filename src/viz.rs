@@ -0,0 +1,686 @@
+/// Draws a solution's intermediate state, for debugging grid puzzles.
+///
+/// A solution describes what it wants to show as a [`Grid`] and passes it to [`frame`]. Frames are
+/// only drawn when the day runs with `--viz`: they are printed to the terminal and saved as
+/// images under `data/viz/<day>/p<part>/`, and `--gif` also puts them together into
+/// `data/viz/<day>-p<part>.gif`. Without the flag `frame` returns right away without building the
+/// grid, and it is always off while benchmarking, so timings are not affected.
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::{env, fs, io};
+
+use anyhow::{ensure, Result};
+
+use crate::Day;
+
+/// Colours cells are highlighted with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Colour {
+    fn ansi(self) -> &'static str {
+        match self {
+            Colour::Red => "\x1b[1;31m",
+            Colour::Green => "\x1b[1;32m",
+            Colour::Yellow => "\x1b[1;33m",
+            Colour::Blue => "\x1b[1;34m",
+            Colour::Magenta => "\x1b[1;35m",
+            Colour::Cyan => "\x1b[1;36m",
+            Colour::White => "\x1b[1;37m",
+        }
+    }
+
+    #[must_use]
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Colour::Red => [230, 60, 60],
+            Colour::Green => [80, 200, 90],
+            Colour::Yellow => [240, 210, 60],
+            Colour::Blue => [70, 120, 230],
+            Colour::Magenta => [210, 80, 210],
+            Colour::Cyan => [70, 210, 220],
+            Colour::White => [250, 250, 250],
+        }
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+const BACKGROUND: [u8; 3] = [15, 15, 35];
+const FOREGROUND: [u8; 3] = [110, 110, 130];
+
+/// A grid of characters, some of them highlighted. Positions are `(x, y)` from the top left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+    highlights: Vec<Option<Colour>>,
+}
+
+impl Grid {
+    #[must_use]
+    pub fn new(width: usize, height: usize, fill: char) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+            highlights: vec![None; width * height],
+        }
+    }
+
+    /// A grid of the lines of `input`. Short lines are padded with spaces.
+    #[must_use]
+    pub fn from_lines(input: &str) -> Self {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut grid = Grid::new(width, lines.len(), ' ');
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                grid.set(x, y, c);
+            }
+        }
+        grid
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.index(x, y).map(|ix| self.cells[ix])
+    }
+
+    /// Positions off the grid are ignored, so callers do not have to check them.
+    pub fn set(&mut self, x: usize, y: usize, c: char) {
+        if let Some(ix) = self.index(x, y) {
+            self.cells[ix] = c;
+        }
+    }
+
+    /// Positions off the grid are ignored, so callers do not have to check them.
+    pub fn highlight(&mut self, x: usize, y: usize, colour: Colour) {
+        if let Some(ix) = self.index(x, y) {
+            self.highlights[ix] = Some(colour);
+        }
+    }
+
+    /// The grid with its highlighted cells coloured, for printing to a terminal.
+    #[must_use]
+    pub fn to_ansi(&self) -> String {
+        let mut res = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let ix = y * self.width + x;
+                match self.highlights[ix] {
+                    Some(colour) => {
+                        res.push_str(colour.ansi());
+                        res.push(self.cells[ix]);
+                        res.push_str(ANSI_RESET);
+                    }
+                    None => res.push(self.cells[ix]),
+                }
+            }
+            res.push('\n');
+        }
+        res
+    }
+
+    /// The grid as an image with a `scale` by `scale` square for every cell. Highlighted cells take
+    /// their colour, empty cells (`.` and spaces) are dark and the others grey.
+    #[must_use]
+    pub fn to_image(&self, scale: usize) -> Image {
+        let scale = scale.max(1);
+        let mut image = Image::new(self.width * scale, self.height * scale, BACKGROUND);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let ix = y * self.width + x;
+                let colour = match (self.highlights[ix], self.cells[ix]) {
+                    (Some(colour), _) => colour.rgb(),
+                    (None, '.' | ' ') => continue,
+                    (None, _) => FOREGROUND,
+                };
+                image.fill_rect(x * scale, y * scale, scale, scale, colour);
+            }
+        }
+        image
+    }
+}
+
+/// An RGB image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    #[must_use]
+    pub fn new(width: usize, height: usize, fill: [u8; 3]) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// Fills a rectangle, clipped to the image.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, colour: [u8; 3]) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                self.pixels[py * self.width + px] = colour;
+            }
+        }
+    }
+
+    /// The image as a binary PPM (`P6`), which most image viewers open.
+    #[must_use]
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut res = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        res.extend(self.pixels.iter().flatten());
+        res
+    }
+
+    /// The image as a PNG. The pixel data is stored rather than compressed, which keeps the encoder
+    /// short at the cost of larger files.
+    #[must_use]
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            // every row starts with its filter type, 0 is none.
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut ihdr = vec![];
+        ihdr.extend((self.width as u32).to_be_bytes());
+        ihdr.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, default compression, filtering and no interlacing.
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        let mut res = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut res, b"IHDR", &ihdr);
+        png_chunk(&mut res, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut res, b"IEND", &[]);
+        res
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for b in bytes {
+        crc ^= u32::from(*b);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Wraps `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut res = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        res.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        res.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        res.extend(len.to_le_bytes());
+        res.extend((!len).to_le_bytes());
+        res.extend(block);
+    }
+
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    res.extend(((b << 16) | a).to_be_bytes());
+    res
+}
+
+/// An animated GIF of `frames`, which loops forever and shows each frame for `delay` hundredths of
+/// a second. The frames have to be the same size and use at most 256 colours between them.
+pub fn to_gif(frames: &[Image], delay: u16) -> Result<Vec<u8>> {
+    ensure!(!frames.is_empty(), "there are no frames to animate");
+    let (width, height) = (frames[0].width, frames[0].height);
+    ensure!(
+        frames
+            .iter()
+            .all(|f| (f.width, f.height) == (width, height)),
+        "the frames are not all the same size"
+    );
+    ensure!(
+        width <= u16::MAX as usize && height <= u16::MAX as usize,
+        "the frames are too large for a GIF"
+    );
+
+    let mut palette: Vec<[u8; 3]> = vec![];
+    for pixel in frames.iter().flat_map(|f| &f.pixels) {
+        if !palette.contains(pixel) {
+            palette.push(*pixel);
+        }
+    }
+    ensure!(
+        palette.len() <= 256,
+        "the frames use {} colours, a GIF can only show 256",
+        palette.len()
+    );
+    // the colour table holds a power of two entries, and LZW codes start at two bits.
+    let bits = (palette.len().next_power_of_two().trailing_zeros() as u8).max(2);
+
+    let mut res = b"GIF89a".to_vec();
+    res.extend((width as u16).to_le_bytes());
+    res.extend((height as u16).to_le_bytes());
+    res.extend([0x80 | ((bits - 1) << 4) | (bits - 1), 0, 0]);
+    for ix in 0..1 << bits {
+        res.extend(palette.get(ix).unwrap_or(&[0, 0, 0]));
+    }
+    // the application extension that makes the animation loop.
+    res.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    for frame in frames {
+        res.extend([0x21, 0xf9, 0x04, 0x00]);
+        res.extend(delay.to_le_bytes());
+        res.extend([0x00, 0x00]);
+
+        res.push(0x2c);
+        res.extend([0, 0, 0, 0]);
+        res.extend((width as u16).to_le_bytes());
+        res.extend((height as u16).to_le_bytes());
+        res.push(0);
+
+        let indices: Vec<u8> = frame
+            .pixels
+            .iter()
+            .map(|p| palette.iter().position(|c| c == p).unwrap() as u8)
+            .collect();
+        res.push(bits);
+        for block in lzw(&indices, bits).chunks(255) {
+            res.push(block.len() as u8);
+            res.extend(block);
+        }
+        res.push(0);
+    }
+
+    res.push(0x3b);
+    Ok(res)
+}
+
+/// Packs variable-width codes into bytes, least significant bit first, the way GIFs expect.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.acc |= u32::from(code) << self.len;
+        self.len += width;
+        while self.len >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+/// GIF flavoured LZW: codes grow from `min_bits + 1` up to 12 bits, then the table starts over.
+fn lzw(indices: &[u8], min_bits: u8) -> Vec<u8> {
+    const MAX_CODE: u16 = 4095;
+    let clear = 1_u16 << min_bits;
+    let end = clear + 1;
+
+    let mut out = BitWriter::default();
+    let mut table: hashbrown::HashMap<(u16, u8), u16> = hashbrown::HashMap::new();
+    let mut width = min_bits + 1;
+    let mut next = end + 1;
+    out.write(clear, width);
+
+    let Some((first, rest)) = indices.split_first() else {
+        out.write(end, width);
+        return out.finish();
+    };
+    let mut prefix = u16::from(*first);
+    for &index in rest {
+        if let Some(code) = table.get(&(prefix, index)) {
+            prefix = *code;
+            continue;
+        }
+        out.write(prefix, width);
+        table.insert((prefix, index), next);
+        if next == 1 << width {
+            width += 1;
+        }
+        if next == MAX_CODE {
+            out.write(clear, width);
+            table.clear();
+            width = min_bits + 1;
+            next = end;
+        }
+        next += 1;
+        prefix = u16::from(index);
+    }
+    out.write(prefix, width);
+    out.write(end, width);
+    out.finish()
+}
+
+/// The file format frames are saved in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Ppm,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Ppm => "ppm",
+        }
+    }
+
+    fn encode(self, image: &Image) -> Vec<u8> {
+        match self {
+            Format::Png => image.to_png(),
+            Format::Ppm => image.to_ppm(),
+        }
+    }
+}
+
+/// Where the frames of the running part go.
+struct Recorder {
+    dir: PathBuf,
+    format: Format,
+    gif: Option<PathBuf>,
+    frames: usize,
+    images: Vec<Image>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// Whether frames are being drawn. Solutions can check this to skip work that only feeds frames.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Makes each cell a few pixels wide, aiming for images about 600 pixels across.
+fn scale_for(grid: &Grid) -> usize {
+    (600 / grid.width.max(grid.height).max(1)).clamp(1, 16)
+}
+
+/// Draws a frame of the running part, if `--viz` is set. `grid` is only called then.
+pub fn frame(label: &str, grid: impl FnOnce() -> Grid) {
+    if !enabled() {
+        return;
+    }
+    let grid = grid();
+    let mut guard = RECORDER.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(recorder) = guard.as_mut() else {
+        return;
+    };
+
+    recorder.frames += 1;
+    eprintln!("{label} (frame {})\n{}", recorder.frames, grid.to_ansi());
+
+    let image = grid.to_image(scale_for(&grid));
+    let path = recorder.dir.join(format!(
+        "{:04}.{}",
+        recorder.frames,
+        recorder.format.extension()
+    ));
+    if let Err(e) = fs::write(&path, recorder.format.encode(&image)) {
+        eprintln!("Could not save {}: {e}", path.display());
+    }
+    if recorder.gif.is_some() {
+        recorder.images.push(image);
+    }
+}
+
+#[must_use]
+pub fn get_viz_dir(day: Day, part: u8) -> PathBuf {
+    Path::new("data")
+        .join("viz")
+        .join(day.to_string())
+        .join(format!("p{part}"))
+}
+
+fn start(day: Day, part: u8, format: Format, gif: bool) -> io::Result<Recorder> {
+    let dir = get_viz_dir(day, part);
+    // frames of an earlier run would get mixed up with the new ones.
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(Recorder {
+        dir,
+        format,
+        gif: gif.then(|| {
+            Path::new("data")
+                .join("viz")
+                .join(format!("{day}-p{part}.gif"))
+        }),
+        frames: 0,
+        images: vec![],
+    })
+}
+
+fn arg_present(flag: &str) -> bool {
+    env::args().any(|x| x == flag)
+}
+
+/// Starts recording the frames of a part, if the day runs with `--viz` or `--gif`.
+pub(crate) fn begin(day: Day, part: u8) {
+    if !arg_present("--viz") && !arg_present("--gif") {
+        return;
+    }
+    if arg_present("--time") {
+        eprintln!("Not drawing frames while benchmarking.");
+        return;
+    }
+    let format = match env::args().skip_while(|x| x != "--viz-format").nth(1) {
+        Some(f) if f == "ppm" => Format::Ppm,
+        _ => Format::Png,
+    };
+    match start(day, part, format, arg_present("--gif")) {
+        Ok(recorder) => {
+            *RECORDER.lock().unwrap_or_else(|e| e.into_inner()) = Some(recorder);
+            ENABLED.store(true, Ordering::Relaxed);
+        }
+        Err(e) => eprintln!("Could not set up frame recording: {e}"),
+    }
+}
+
+/// Stops recording, and puts the frames together into a GIF if `--gif` is set.
+pub(crate) fn end() {
+    ENABLED.store(false, Ordering::Relaxed);
+    let Some(recorder) = RECORDER.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return;
+    };
+    if recorder.frames == 0 {
+        eprintln!("The part did not draw any frames.");
+        return;
+    }
+    eprintln!(
+        "Saved {} frame(s) to {}",
+        recorder.frames,
+        recorder.dir.display()
+    );
+    if let Some(path) = recorder.gif {
+        match to_gif(&recorder.images, 10).and_then(|gif| Ok(fs::write(&path, gif)?)) {
+            Ok(()) => eprintln!("Saved the animation to {}", path.display()),
+            Err(e) => eprintln!("Could not save the animation: {e:#}"),
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{crc32, lzw, to_gif, zlib_stored, Colour, Grid, Image};
+
+    fn grid() -> Grid {
+        let mut grid = Grid::from_lines("#..\n.#\n");
+        grid.highlight(1, 1, Colour::Red);
+        grid.highlight(7, 7, Colour::Red);
+        grid.set(2, 1, '*');
+        grid
+    }
+
+    #[test]
+    fn builds_grids() {
+        let grid = grid();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(0, 1), Some('.'));
+        assert_eq!(grid.get(2, 1), Some('*'));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.to_ansi(), "#..\n.\x1b[1;31m#\x1b[0m*\n");
+    }
+
+    #[test]
+    fn draws_images() {
+        let image = grid().to_image(2);
+        assert_eq!((image.width, image.height), (6, 4));
+        assert_eq!(image.pixels[0], super::FOREGROUND);
+        assert_eq!(image.pixels[2], super::BACKGROUND);
+        assert_eq!(image.pixels[3 * 6 + 3], Colour::Red.rgb());
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n6 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 4 * 3);
+    }
+
+    #[test]
+    fn encodes_pngs() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        // one stored block: header, length and its complement, the data and the checksum.
+        assert_eq!(
+            zlib_stored(b"abc"),
+            [0x78, 0x01, 1, 3, 0, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27]
+        );
+        let png = grid().to_image(1).to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x03\0\0\0\x02"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+    }
+
+    /// Decodes GIF LZW the way a viewer would.
+    fn unlzw(bytes: &[u8], min_bits: u8) -> Vec<u8> {
+        let clear = 1_usize << min_bits;
+        let mut bit = 0;
+        let mut read = |width: u8| {
+            let mut code = 0;
+            for i in 0..width as usize {
+                let b = (bytes[(bit + i) / 8] >> ((bit + i) % 8)) & 1;
+                code |= usize::from(b) << i;
+            }
+            bit += width as usize;
+            code
+        };
+        let mut res = vec![];
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut width = min_bits + 1;
+        let mut prev: Option<Vec<u8>> = None;
+        loop {
+            let code = read(width);
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.extend([vec![], vec![]]);
+                width = min_bits + 1;
+                prev = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return res;
+            }
+            let entry = match (table.get(code), &prev) {
+                (Some(e), _) => e.clone(),
+                (None, Some(p)) => [p.clone(), vec![p[0]]].concat(),
+                (None, None) => panic!("invalid code"),
+            };
+            if let Some(p) = prev {
+                table.push([p, vec![entry[0]]].concat());
+                if table.len() == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+            res.extend(&entry);
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trips() {
+        let mut state = 7_u32;
+        let long: Vec<u8> = (0..20_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                ((state >> 16) % 4) as u8
+            })
+            .collect();
+        for indices in [
+            vec![],
+            vec![1],
+            vec![0, 0, 0, 0, 1, 1, 1, 3, 0, 0, 0, 0],
+            long,
+        ] {
+            assert_eq!(unlzw(&lzw(&indices, 2), 2), indices);
+        }
+    }
+
+    #[test]
+    fn animates_frames() {
+        let frames = [grid().to_image(1), Grid::from_lines("...\n...").to_image(1)];
+        let gif = to_gif(&frames, 10).unwrap();
+        assert!(gif.starts_with(b"GIF89a\x03\x00\x02\x00"));
+        assert!(gif.ends_with(&[0x3b]));
+        assert_eq!(gif.windows(2).filter(|w| w == b"\x21\xf9").count(), 2);
+
+        assert!(to_gif(&[], 10).is_err());
+        let other_size = Image::new(1, 1, [0, 0, 0]);
+        assert!(to_gif(&[frames[0].clone(), other_size], 10).is_err());
+    }
+}